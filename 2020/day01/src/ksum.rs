@@ -0,0 +1,364 @@
+use std::collections::HashMap;
+use std::ops::ControlFlow;
+
+/// Ways of picking `k` entries that add up to `target`. Each entry is used at most once, and
+/// solutions are distinct multisets of values, so duplicated entries don't repeat a
+/// solution; each one is reported in ascending order.
+pub struct KSum {
+    entries: Vec<i64>,
+    k: usize,
+    target: i64,
+}
+
+pub fn find_k_sum(entries: &[i64], k: usize, target: i64) -> KSum {
+    let mut entries = entries.to_vec();
+    entries.sort_unstable();
+    KSum { entries, k, target }
+}
+
+impl KSum {
+    pub fn first(&self) -> Option<Vec<i64>> {
+        let mut first = None;
+        self.search(&mut |solution| {
+            first = Some(solution.to_vec());
+            ControlFlow::Break(())
+        });
+        first
+    }
+
+    pub fn all(&self) -> Vec<Vec<i64>> {
+        let mut all = vec![];
+        self.search(&mut |solution| {
+            all.push(solution.to_vec());
+            ControlFlow::Continue(())
+        });
+        all
+    }
+
+    pub fn count(&self) -> u64 {
+        if self.k >= 4 {
+            return self.meet_in_the_middle().count(self.target as i128);
+        }
+        let mut count = 0;
+        self.search(&mut |_| {
+            count += 1;
+            ControlFlow::Continue(())
+        });
+        count
+    }
+
+    fn search<F>(&self, visit: &mut F)
+    where
+        F: FnMut(&[i64]) -> ControlFlow<()>,
+    {
+        if self.k > self.entries.len() {
+            return;
+        }
+        match self.k {
+            0 => {
+                if self.target == 0 {
+                    let _ = visit(&[]);
+                }
+            }
+            1 => {
+                if self.entries.binary_search(&self.target).is_ok() {
+                    let _ = visit(&[self.target]);
+                }
+            }
+            2 | 3 => {
+                let mut chosen = vec![];
+                let _ = self.fix_then_pair(None, self.target as i128, &mut chosen, visit);
+            }
+            _ => {
+                let _ = self.meet_in_the_middle().search(self.target as i128, visit);
+            }
+        }
+    }
+
+    // fixes all but the last two entries, smallest first, then finds the pair by walking
+    // in from both ends of what's left: O(n^(k-1)). Sums are kept as i128, as entries near
+    // the ends of the i64 range can take them past it on the way to the target.
+    fn fix_then_pair<F>(
+        &self,
+        prev: Option<usize>,
+        remaining: i128,
+        chosen: &mut Vec<i64>,
+        visit: &mut F,
+    ) -> ControlFlow<()>
+    where
+        F: FnMut(&[i64]) -> ControlFlow<()>,
+    {
+        let start = prev.map_or(0, |p| p + 1);
+        if chosen.len() + 2 < self.k {
+            for idx in start..self.entries.len() {
+                if !canonical(&self.entries, prev, idx) {
+                    continue;
+                }
+                chosen.push(self.entries[idx]);
+                let flow = self.fix_then_pair(
+                    Some(idx),
+                    remaining - self.entries[idx] as i128,
+                    chosen,
+                    visit,
+                );
+                chosen.pop();
+                flow?;
+            }
+            return ControlFlow::Continue(());
+        }
+
+        let (mut lo, mut hi) = (start, self.entries.len());
+        while lo + 1 < hi {
+            let sum = self.entries[lo] as i128 + self.entries[hi - 1] as i128;
+            if sum < remaining {
+                lo += 1;
+            } else if sum > remaining {
+                hi -= 1;
+            } else {
+                chosen.push(self.entries[lo]);
+                chosen.push(self.entries[hi - 1]);
+                let flow = visit(chosen);
+                chosen.truncate(chosen.len() - 2);
+                flow?;
+                // step past every copy of both values so the pair isn't reported again
+                let (low, high) = (self.entries[lo], self.entries[hi - 1]);
+                while lo < hi && self.entries[lo] == low {
+                    lo += 1;
+                }
+                while hi > lo && self.entries[hi - 1] == high {
+                    hi -= 1;
+                }
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn meet_in_the_middle(&self) -> MeetInTheMiddle<'_> {
+        let left_size = self.k / 2;
+        let mut left: HashMap<i128, Vec<Vec<usize>>> = HashMap::new();
+        let mut combo = vec![];
+        for_each_combo(&self.entries, None, left_size, &mut combo, &mut |combo| {
+            let sum = combo.iter().map(|&idx| self.entries[idx] as i128).sum();
+            left.entry(sum).or_default().push(combo.to_vec());
+        });
+        // combos come out in lexicographic order, but sorting by the last index is
+        // what lets `count` binary search them
+        for combos in left.values_mut() {
+            combos.sort_by_key(|combo| *combo.last().unwrap());
+        }
+
+        MeetInTheMiddle {
+            entries: &self.entries,
+            left,
+            right_size: self.k - left_size,
+        }
+    }
+}
+
+// A k-combination of indices into the sorted entries is split into its lowest k/2 indices
+// (`left`, all precomputed by sum) and the rest. Each multiset of values is only counted in
+// one form: the one using the earliest copies of every value, which is what `canonical` checks.
+struct MeetInTheMiddle<'a> {
+    entries: &'a [i64],
+    left: HashMap<i128, Vec<Vec<usize>>>,
+    right_size: usize,
+}
+
+impl MeetInTheMiddle<'_> {
+    fn search<F>(&self, target: i128, visit: &mut F) -> ControlFlow<()>
+    where
+        F: FnMut(&[i64]) -> ControlFlow<()>,
+    {
+        let mut solution = vec![];
+        self.for_each_right(|right, sum| {
+            let lefts = match self.left.get(&(target - sum)) {
+                Some(lefts) => lefts,
+                None => return ControlFlow::Continue(()),
+            };
+            for left in lefts {
+                let last = *left.last().unwrap();
+                if last >= right[0] || !canonical(self.entries, Some(last), right[0]) {
+                    continue;
+                }
+                solution.clear();
+                solution.extend(left.iter().chain(right).map(|&idx| self.entries[idx]));
+                visit(&solution)?;
+            }
+            ControlFlow::Continue(())
+        })
+    }
+
+    fn count(&self, target: i128) -> u64 {
+        let mut count = 0;
+        let _ = self.for_each_right(|right, sum| {
+            if let Some(lefts) = self.left.get(&(target - sum)) {
+                count += self.matching_lefts(lefts, right[0]) as u64;
+            }
+            ControlFlow::Continue(())
+        });
+        count
+    }
+
+    // how many of the lefts (sorted by last index) can be followed by `first`
+    fn matching_lefts(&self, lefts: &[Vec<usize>], first: usize) -> usize {
+        let last_below = |bound: usize| lefts.partition_point(|left| *left.last().unwrap() < bound);
+        if first == 0 || self.entries[first] != self.entries[first - 1] {
+            last_below(first)
+        } else {
+            // a repeated value can only be picked straight after the copy before it
+            last_below(first) - last_below(first - 1)
+        }
+    }
+
+    // the first right index may be any entry; the rest of the right half follows the
+    // usual rule, since they come after an index from the same half
+    fn for_each_right<F>(&self, mut f: F) -> ControlFlow<()>
+    where
+        F: FnMut(&[usize], i128) -> ControlFlow<()>,
+    {
+        let mut combo = vec![];
+        for first in 0..self.entries.len() {
+            combo.push(first);
+            let mut flow = ControlFlow::Continue(());
+            for_each_combo(
+                self.entries,
+                Some(first),
+                self.right_size - 1,
+                &mut combo,
+                &mut |combo| {
+                    if flow.is_continue() {
+                        let sum = combo.iter().map(|&idx| self.entries[idx] as i128).sum();
+                        flow = f(combo, sum);
+                    }
+                },
+            );
+            combo.pop();
+            flow?;
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+// Picking `idx` straight after `prev` is canonical unless it's a repeat of a value that
+// could have been picked earlier: copies of a value are always used from the first one on.
+fn canonical(entries: &[i64], prev: Option<usize>, idx: usize) -> bool {
+    let start = prev.map_or(0, |p| p + 1);
+    idx == start || entries[idx] != entries[idx - 1]
+}
+
+// every canonical way to extend `combo` by `size` more indices, all after `prev`
+fn for_each_combo<F>(
+    entries: &[i64],
+    prev: Option<usize>,
+    size: usize,
+    combo: &mut Vec<usize>,
+    f: &mut F,
+) where
+    F: FnMut(&[usize]),
+{
+    if size == 0 {
+        f(combo);
+        return;
+    }
+    let start = prev.map_or(0, |p| p + 1);
+    for idx in start..entries.len() {
+        if entries.len() - idx < size {
+            break;
+        }
+        if canonical(entries, prev, idx) {
+            combo.push(idx);
+            for_each_combo(entries, Some(idx), size - 1, combo, f);
+            combo.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    // every distinct multiset of k entries adding up to the target, the slow way
+    fn brute_force(entries: &[i64], k: usize, target: i64) -> BTreeSet<Vec<i64>> {
+        let mut found = BTreeSet::new();
+        for mask in 0u32..1 << entries.len() {
+            if mask.count_ones() as usize != k {
+                continue;
+            }
+            let mut picked: Vec<_> = (0..entries.len())
+                .filter(|idx| mask & 1 << idx != 0)
+                .map(|idx| entries[idx])
+                .collect();
+            if picked.iter().map(|&e| e as i128).sum::<i128>() == target as i128 {
+                picked.sort_unstable();
+                found.insert(picked);
+            }
+        }
+        found
+    }
+
+    fn check(entries: &[i64], k: usize, target: i64) {
+        let expected = brute_force(entries, k, target);
+        let solver = find_k_sum(entries, k, target);
+        let all = solver.all();
+        assert_eq!(all.len(), expected.len(), "k = {}, target = {}", k, target);
+        assert_eq!(all.into_iter().collect::<BTreeSet<_>>(), expected);
+        assert_eq!(solver.count(), expected.len() as u64);
+        match solver.first() {
+            Some(first) => assert!(expected.contains(&first)),
+            None => assert!(expected.is_empty()),
+        }
+    }
+
+    #[test]
+    fn matches_brute_force_with_duplicates() {
+        let entries = [3, -2, 7, 3, 0, 5, -2, 3, 10, 1, 7, -6, 4];
+        for k in 2..=5 {
+            for target in -10..=25 {
+                check(&entries, k, target);
+            }
+        }
+    }
+
+    #[test]
+    fn matches_brute_force_on_the_puzzle_example() {
+        let entries = [1721, 979, 366, 299, 675, 1456];
+        for k in 2..=4 {
+            for target in [2020, 3000, 1, 4000] {
+                check(&entries, k, target);
+            }
+        }
+        assert_eq!(find_k_sum(&entries, 2, 2020).first(), Some(vec![299, 1721]));
+        assert_eq!(
+            find_k_sum(&entries, 3, 2020).first(),
+            Some(vec![366, 675, 979])
+        );
+    }
+
+    #[test]
+    fn no_solutions() {
+        check(&[1, 2, 4, 8, 16], 3, 100);
+        check(&[5, 5, 5, 5], 4, 21);
+        check(&[1, 2], 3, 3);
+        assert_eq!(find_k_sum(&[1, 2, 3, 4, 5, 6], 4, 100).count(), 0);
+    }
+
+    #[test]
+    fn extreme_entries_dont_overflow() {
+        let entries = [
+            i64::MAX,
+            i64::MIN,
+            i64::MAX - 1,
+            i64::MIN + 1,
+            1,
+            -1,
+            0,
+            i64::MAX,
+        ];
+        for k in 2..=4 {
+            for target in [i64::MAX, i64::MIN, 0, -1, 1, i64::MAX - 2, i64::MIN + 2] {
+                check(&entries, k, target);
+            }
+        }
+    }
+}
@@ -0,0 +1,34 @@
+pub mod ksum;
+pub mod subset;
+
+use common::parse_vec;
+use common::solution::{Answer, Solution};
+use ksum::find_k_sum;
+
+pub const TARGET: i64 = 2020;
+
+pub struct Day1;
+
+impl Solution for Day1 {
+    type Input = Vec<i64>;
+    type Part1 = Answer<i64>;
+    type Part2 = Answer<i64>;
+
+    fn parse(src: &str) -> Result<Vec<i64>, String> {
+        parse_vec(src)
+    }
+
+    fn part1(src: &Vec<i64>) -> Answer<i64> {
+        Answer(product_of_k_sum(src, 2, TARGET))
+    }
+
+    fn part2(src: &Vec<i64>) -> Answer<i64> {
+        Answer(product_of_k_sum(src, 3, TARGET))
+    }
+}
+
+pub fn product_of_k_sum(entries: &[i64], k: usize, target: i64) -> Option<i64> {
+    find_k_sum(entries, k, target)
+        .first()
+        .map(|solution| solution.iter().product())
+}
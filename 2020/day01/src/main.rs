@@ -0,0 +1,113 @@
+use aoc2020_day01::ksum::find_k_sum;
+use aoc2020_day01::subset::SubsetSums;
+use aoc2020_day01::{Day1, TARGET};
+use common::cli::{self, Args, Cli};
+
+const USAGE: &str = "usage:
+  aoc2020-day01
+  aoc2020-day01 ksum <k> [target] [--all | --count] [--input FILE]
+  aoc2020-day01 subset [target] [--input FILE]
+  aoc2020-day01 closest [target] [--input FILE]
+
+The target defaults to 2020 and the entries to the day's puzzle input. `subset` finds
+entries of any number adding up to the target; `closest` gets as near to it as possible
+without going over.";
+
+const CLI: Cli = Cli {
+    usage: USAGE,
+    switches: &["--all", "--count"],
+    options: &[],
+};
+
+fn main() {
+    cli::main::<Day1>(2020, 1, &CLI, run);
+}
+
+fn run(command: &str, args: &Args) -> Result<(), String> {
+    if args.switch("--all") && args.switch("--count") {
+        return Err(args.usage_error("--all and --count can't be used together".into()));
+    }
+    match command {
+        "ksum" => ksum(args),
+        "subset" => subset(args),
+        "closest" => closest(args),
+        _ => Err(args.unknown_command(command)),
+    }
+}
+
+fn ksum(args: &Args) -> Result<(), String> {
+    let (k, target) = match args.positional[..] {
+        [k] => (args.parse_arg::<usize>(k, "k")?, TARGET),
+        [k, target] => (args.parse_arg(k, "k")?, args.parse_arg(target, "target")?),
+        _ => return Err(args.usage_error("Expected <k> [target]".into())),
+    };
+    let entries = entries(args)?;
+    let query = find_k_sum(&entries, k, target);
+
+    if args.switch("--count") {
+        println!("{}", query.count());
+        return Ok(());
+    }
+    let solutions = if args.switch("--all") {
+        query.all()
+    } else {
+        query.first().into_iter().collect()
+    };
+    if solutions.is_empty() {
+        return Err(format!("No {} entries sum to {}", k, target));
+    }
+    for solution in solutions {
+        println!("{} = {}", target, join(&solution));
+    }
+    Ok(())
+}
+
+fn subset(args: &Args) -> Result<(), String> {
+    let target = target(args)?;
+    let sums = SubsetSums::new(&entries(args)?)?;
+    let subset = sums
+        .subset_summing_to(target)
+        .ok_or(format!("No subset of the entries sums to {}", target))?;
+    println!("{} = {}", target, join(&subset));
+    Ok(())
+}
+
+fn closest(args: &Args) -> Result<(), String> {
+    let target = target(args)?;
+    let sums = SubsetSums::new(&entries(args)?)?;
+    let (sum, subset) = sums.closest_not_exceeding(target).ok_or(format!(
+        "Every subset of the entries sums to more than {}",
+        target
+    ))?;
+    println!(
+        "{} = {} ({} under {})",
+        sum,
+        join(&subset),
+        target - sum,
+        target
+    );
+    Ok(())
+}
+
+fn target(args: &Args) -> Result<i64, String> {
+    match args.positional[..] {
+        [] => Ok(TARGET),
+        [target] => args.parse_arg(target, "target"),
+        _ => Err(args.usage_error("Expected at most one target".into())),
+    }
+}
+
+fn entries(args: &Args) -> Result<Vec<i64>, String> {
+    args.load::<Day1>(2020, 1)
+}
+
+fn join(entries: &[i64]) -> String {
+    if entries.is_empty() {
+        return "(no entries)".into();
+    }
+    entries
+        .iter()
+        .map(|entry| entry.to_string())
+        .collect::<Vec<_>>()
+        .join(" + ")
+}
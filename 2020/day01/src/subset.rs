@@ -0,0 +1,256 @@
+// above this many possible sums the tables would run to hundreds of megabytes
+const MAX_SUMS: u64 = 1 << 24;
+const NONE: u32 = u32::MAX;
+
+/// Every sum reachable by a subset of the entries, each entry used at most once, with
+/// enough bookkeeping to rebuild a subset for any of them. The empty subset counts, so
+/// 0 is always reachable. Built in O(n * range / 64), where range is the spread between
+/// the sum of the negative entries and the sum of the positive ones.
+pub struct SubsetSums {
+    entries: Vec<i64>,
+    // the smallest reachable sum, which bit 0 stands for
+    min_sum: i64,
+    reachable: Vec<u64>,
+    // the index of the entry that first made each sum reachable
+    first_entry: Vec<u32>,
+}
+
+impl SubsetSums {
+    pub fn new(entries: &[i64]) -> Result<Self, String> {
+        let min_sum: i64 = entries.iter().filter(|&&e| e < 0).sum();
+        let max_sum: i64 = entries.iter().filter(|&&e| e > 0).sum();
+        let n_sums = (max_sum as i128 - min_sum as i128 + 1) as u64;
+        if n_sums > MAX_SUMS {
+            return Err(format!(
+                "The entries can sum to anything from {} to {}, too wide a range to tabulate",
+                min_sum, max_sum
+            ));
+        }
+
+        let n_sums = n_sums as usize;
+        let mut sums = SubsetSums {
+            entries: entries.to_vec(),
+            min_sum,
+            reachable: vec![0; n_sums.div_ceil(64)],
+            first_entry: vec![NONE; n_sums],
+        };
+        let zero = (-min_sum) as usize;
+        sums.reachable[zero / 64] |= 1 << (zero % 64);
+
+        for (idx, &entry) in entries.iter().enumerate() {
+            let shifted = shift(&sums.reachable, entry, n_sums);
+            for (word_idx, (word, shifted)) in sums.reachable.iter_mut().zip(shifted).enumerate() {
+                let mut added = shifted & !*word;
+                *word |= added;
+                while added != 0 {
+                    let bit = added.trailing_zeros() as usize;
+                    sums.first_entry[word_idx * 64 + bit] = idx as u32;
+                    added &= added - 1;
+                }
+            }
+        }
+
+        Ok(sums)
+    }
+
+    pub fn is_reachable(&self, sum: i64) -> bool {
+        self.index(sum).is_some_and(|idx| self.bit(idx))
+    }
+
+    // the entries of one subset adding up to `target`, smallest first
+    pub fn subset_summing_to(&self, target: i64) -> Option<Vec<i64>> {
+        let idx = self.index(target).filter(|&idx| self.bit(idx))?;
+        Some(self.witness(idx))
+    }
+
+    // the highest reachable sum that doesn't exceed `target`, with a subset making it up
+    pub fn closest_not_exceeding(&self, target: i64) -> Option<(i64, Vec<i64>)> {
+        if target < self.min_sum {
+            return None;
+        }
+        let top = self.index(target).unwrap_or(self.first_entry.len() - 1);
+        let idx = self.highest_reachable_at_or_below(top)?;
+        Some((self.sum(idx), self.witness(idx)))
+    }
+
+    // each sum was first reached by adding one entry to a sum that was reachable
+    // without it, so walking those entries back down to 0 uses each one at most once
+    fn witness(&self, mut idx: usize) -> Vec<i64> {
+        let mut subset = vec![];
+        while self.first_entry[idx] != NONE {
+            let entry = self.entries[self.first_entry[idx] as usize];
+            subset.push(entry);
+            idx = (idx as i64 - entry) as usize;
+        }
+        subset.sort_unstable();
+        subset
+    }
+
+    fn highest_reachable_at_or_below(&self, idx: usize) -> Option<usize> {
+        let (mut word_idx, bit) = (idx / 64, idx % 64);
+        let mask = if bit == 63 { !0 } else { (1 << (bit + 1)) - 1 };
+        let mut word = self.reachable[word_idx] & mask;
+        loop {
+            if word != 0 {
+                return Some(word_idx * 64 + 63 - word.leading_zeros() as usize);
+            }
+            if word_idx == 0 {
+                return None;
+            }
+            word_idx -= 1;
+            word = self.reachable[word_idx];
+        }
+    }
+
+    fn index(&self, sum: i64) -> Option<usize> {
+        let idx = sum.checked_sub(self.min_sum)?;
+        if idx < 0 || idx as usize >= self.first_entry.len() {
+            None
+        } else {
+            Some(idx as usize)
+        }
+    }
+
+    fn sum(&self, idx: usize) -> i64 {
+        self.min_sum + idx as i64
+    }
+
+    fn bit(&self, idx: usize) -> bool {
+        self.reachable[idx / 64] & (1 << (idx % 64)) != 0
+    }
+}
+
+// the bitset moved `by` bits up (or down, if negative), with anything past `len` dropped
+fn shift(bits: &[u64], by: i64, len: usize) -> Vec<u64> {
+    let mut shifted = vec![0; bits.len()];
+    let (words, offset) = (
+        (by.unsigned_abs() / 64) as usize,
+        (by.unsigned_abs() % 64) as u32,
+    );
+    for (idx, &word) in bits.iter().enumerate() {
+        if word == 0 {
+            continue;
+        }
+        if by >= 0 {
+            let target = idx + words;
+            if target < shifted.len() {
+                shifted[target] |= word << offset;
+            }
+            if offset > 0 && target + 1 < shifted.len() {
+                shifted[target + 1] |= word >> (64 - offset);
+            }
+        } else if idx >= words {
+            let target = idx - words;
+            shifted[target] |= word >> offset;
+            if offset > 0 && target > 0 {
+                shifted[target - 1] |= word << (64 - offset);
+            }
+        }
+    }
+    if !len.is_multiple_of(64) {
+        *shifted.last_mut().unwrap() &= (1 << (len % 64)) - 1;
+    }
+    shifted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // whether `subset` can be picked from `entries`, each entry at most once
+    fn is_drawn_from(subset: &[i64], entries: &[i64]) -> bool {
+        let mut left = entries.to_vec();
+        subset
+            .iter()
+            .all(|value| match left.iter().position(|e| e == value) {
+                Some(idx) => {
+                    left.swap_remove(idx);
+                    true
+                }
+                None => false,
+            })
+    }
+
+    fn brute_force_sums(entries: &[i64]) -> Vec<i64> {
+        let mut sums: Vec<i64> = (0u32..1 << entries.len())
+            .map(|mask| {
+                (0..entries.len())
+                    .filter(|idx| mask & 1 << idx != 0)
+                    .map(|idx| entries[idx])
+                    .sum()
+            })
+            .collect();
+        sums.sort_unstable();
+        sums.dedup();
+        sums
+    }
+
+    #[test]
+    fn witnesses_add_up_to_the_target() {
+        let entries = [7, -3, 12, 7, 1, -8, 30, 4];
+        let sums = SubsetSums::new(&entries).unwrap();
+        let reachable = brute_force_sums(&entries);
+        for target in -20..=70 {
+            assert_eq!(sums.is_reachable(target), reachable.contains(&target));
+            match sums.subset_summing_to(target) {
+                Some(subset) => {
+                    assert_eq!(subset.iter().sum::<i64>(), target);
+                    assert!(is_drawn_from(&subset, &entries), "{:?}", subset);
+                    assert!(subset.windows(2).all(|pair| pair[0] <= pair[1]));
+                }
+                None => assert!(!reachable.contains(&target)),
+            }
+        }
+        assert_eq!(sums.subset_summing_to(0), Some(vec![]));
+    }
+
+    #[test]
+    fn unreachable_targets() {
+        let sums = SubsetSums::new(&[2, 4, 6]).unwrap();
+        assert_eq!(sums.subset_summing_to(5), None);
+        assert_eq!(sums.subset_summing_to(13), None);
+        assert_eq!(sums.subset_summing_to(-2), None);
+        assert_eq!(sums.subset_summing_to(i64::MIN), None);
+        assert_eq!(sums.closest_not_exceeding(-1), None);
+    }
+
+    #[test]
+    fn closest_sum_with_ties() {
+        // 3 and 1 + 2 both make 3; either is a fine witness
+        let entries = [1, 2, 3, 10];
+        let sums = SubsetSums::new(&entries).unwrap();
+        let (sum, subset) = sums.closest_not_exceeding(3).unwrap();
+        assert_eq!(sum, 3);
+        assert_eq!(subset.iter().sum::<i64>(), 3);
+        assert!(is_drawn_from(&subset, &entries));
+
+        // 6 is 1 + 2 + 3 only, the best below 9
+        assert_eq!(sums.closest_not_exceeding(9), Some((6, vec![1, 2, 3])));
+        // past the highest sum, everything is used
+        assert_eq!(
+            sums.closest_not_exceeding(100),
+            Some((16, vec![1, 2, 3, 10]))
+        );
+    }
+
+    #[test]
+    fn closest_sum_across_word_boundaries() {
+        let entries = [-70, 64, 65, 130];
+        let sums = SubsetSums::new(&entries).unwrap();
+        let reachable = brute_force_sums(&entries);
+        for target in -80..200 {
+            let best = reachable.iter().copied().filter(|&s| s <= target).max();
+            let found = sums.closest_not_exceeding(target);
+            assert_eq!(
+                found.as_ref().map(|(sum, _)| *sum),
+                best,
+                "target {}",
+                target
+            );
+            if let Some((sum, subset)) = found {
+                assert_eq!(subset.iter().sum::<i64>(), sum);
+                assert!(is_drawn_from(&subset, &entries));
+            }
+        }
+    }
+}
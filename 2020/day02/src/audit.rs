@@ -0,0 +1,417 @@
+use crate::policy::{Policy, Violation};
+use crate::PasswordLine;
+use common::escape::csv_field;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::rc::Rc;
+use std::str::FromStr;
+
+pub enum Status {
+    Valid,
+    // every policy the password broke, by the name it was audited under
+    Invalid(Vec<(String, Violation)>),
+    Malformed(String),
+}
+
+pub struct AuditLine {
+    // 1-based, counting blank lines so it matches the file
+    pub line: usize,
+    pub text: String,
+    // as far as it could be read, even from a malformed line
+    pub required: Option<char>,
+    pub status: Status,
+}
+
+#[derive(Default)]
+pub struct CharSummary {
+    pub lines: usize,
+    pub valid: usize,
+    pub invalid: usize,
+    pub malformed: usize,
+    pub invalid_by_policy: BTreeMap<String, usize>,
+}
+
+/// Every non-blank line of a password database, classified against a set of named
+/// policies: a line is valid only if it parses and passes all of them.
+pub struct Audit {
+    pub lines: Vec<AuditLine>,
+    pub summary: BTreeMap<Option<char>, CharSummary>,
+}
+
+pub fn audit(src: &str, policies: &[(String, Rc<dyn Policy>)]) -> Audit {
+    let mut lines = vec![];
+    let mut summary: BTreeMap<Option<char>, CharSummary> = BTreeMap::new();
+    for (idx, text) in src.split('\n').enumerate() {
+        let text = text.trim();
+        if text.is_empty() {
+            continue;
+        }
+        let (required, status) = match PasswordLine::from_str(text) {
+            Ok(password) => (
+                Some(password.requirement.required),
+                classify(&password, policies),
+            ),
+            Err(e) => (guess_required(text), Status::Malformed(e)),
+        };
+
+        let stats = summary.entry(required).or_default();
+        stats.lines += 1;
+        match &status {
+            Status::Valid => stats.valid += 1,
+            Status::Invalid(violations) => {
+                stats.invalid += 1;
+                for (name, _) in violations {
+                    *stats.invalid_by_policy.entry(name.clone()).or_default() += 1;
+                }
+            }
+            Status::Malformed(_) => stats.malformed += 1,
+        }
+        lines.push(AuditLine {
+            line: idx + 1,
+            text: text.into(),
+            required,
+            status,
+        });
+    }
+    Audit { lines, summary }
+}
+
+fn classify(password: &PasswordLine, policies: &[(String, Rc<dyn Policy>)]) -> Status {
+    for (name, policy) in policies {
+        if let Err(e) = policy.well_formed(password) {
+            return Status::Malformed(format!("{}: {}", name, e));
+        }
+    }
+    let violations: Vec<_> = policies
+        .iter()
+        .filter_map(|(name, policy)| policy.check(password).err().map(|v| (name.clone(), v)))
+        .collect();
+    if violations.is_empty() {
+        Status::Valid
+    } else {
+        Status::Invalid(violations)
+    }
+}
+
+// the character between the range and the ':', if there's exactly one
+fn guess_required(text: &str) -> Option<char> {
+    let requirement = text.split(':').next()?;
+    let mut chars = requirement.split_whitespace().nth(1)?.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(c),
+        _ => None,
+    }
+}
+
+impl Status {
+    fn name(&self) -> &'static str {
+        match self {
+            Status::Valid => "valid",
+            Status::Invalid(_) => "invalid",
+            Status::Malformed(_) => "malformed",
+        }
+    }
+
+    fn policies(&self) -> String {
+        match self {
+            Status::Invalid(violations) => violations
+                .iter()
+                .map(|(name, _)| name.as_str())
+                .collect::<Vec<_>>()
+                .join(";"),
+            _ => String::new(),
+        }
+    }
+
+    fn reason(&self) -> String {
+        match self {
+            Status::Valid => String::new(),
+            Status::Invalid(violations) => violations
+                .iter()
+                .map(|(_, violation)| violation.to_string())
+                .collect::<Vec<_>>()
+                .join("; "),
+            Status::Malformed(e) => e.clone(),
+        }
+    }
+}
+
+impl Audit {
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("line,status,policy,reason,text\n");
+        for line in &self.lines {
+            let status = &line.status;
+            let fields = [
+                line.line.to_string(),
+                status.name().into(),
+                status.policies(),
+                status.reason(),
+                line.text.clone(),
+            ];
+            let fields: Vec<_> = fields.iter().map(|field| csv_field(field)).collect();
+            out.push_str(&fields.join(","));
+            out.push('\n');
+        }
+        out
+    }
+
+    pub fn summary_csv(&self) -> String {
+        let mut out = String::from("required,lines,valid,invalid,malformed,invalid_by_policy\n");
+        for (required, stats) in &self.summary {
+            let by_policy: Vec<_> = stats
+                .invalid_by_policy
+                .iter()
+                .map(|(name, count)| format!("{}={}", name, count))
+                .collect();
+            let required = required.map(String::from).unwrap_or_default();
+            writeln!(
+                out,
+                "{},{},{},{},{},{}",
+                csv_field(&required),
+                stats.lines,
+                stats.valid,
+                stats.invalid,
+                stats.malformed,
+                csv_field(&by_policy.join(";"))
+            )
+            .unwrap();
+        }
+        out
+    }
+
+    pub fn to_json(&self, with_lines: bool) -> String {
+        let lines = with_lines.then(|| self.lines.iter().map(JsonLine::new).collect());
+        let summary = self
+            .summary
+            .iter()
+            .map(|(&required, stats)| JsonSummary {
+                required,
+                lines: stats.lines,
+                valid: stats.valid,
+                invalid: stats.invalid,
+                malformed: stats.malformed,
+                invalid_by_policy: &stats.invalid_by_policy,
+            })
+            .collect();
+        let mut out = serde_json::to_string(&JsonAudit { lines, summary }).unwrap();
+        out.push('\n');
+        out
+    }
+}
+
+// what `to_json` writes, field for field
+#[derive(Serialize)]
+struct JsonAudit<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    lines: Option<Vec<JsonLine<'a>>>,
+    summary: Vec<JsonSummary<'a>>,
+}
+
+#[derive(Serialize)]
+struct JsonLine<'a> {
+    line: usize,
+    status: &'static str,
+    required: Option<char>,
+    text: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    violations: Option<Vec<JsonViolation<'a>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct JsonViolation<'a> {
+    policy: &'a str,
+    rule: &'a str,
+    detail: &'a str,
+}
+
+#[derive(Serialize)]
+struct JsonSummary<'a> {
+    required: Option<char>,
+    lines: usize,
+    valid: usize,
+    invalid: usize,
+    malformed: usize,
+    invalid_by_policy: &'a BTreeMap<String, usize>,
+}
+
+impl<'a> JsonLine<'a> {
+    fn new(line: &'a AuditLine) -> Self {
+        let (violations, reason) = match &line.status {
+            Status::Valid => (None, None),
+            Status::Invalid(violations) => {
+                let violations = violations
+                    .iter()
+                    .map(|(policy, violation)| JsonViolation {
+                        policy,
+                        rule: &violation.rule,
+                        detail: &violation.detail,
+                    })
+                    .collect();
+                (Some(violations), None)
+            }
+            Status::Malformed(e) => (None, Some(&e[..])),
+        };
+        JsonLine {
+            line: line.line,
+            status: line.status.name(),
+            required: line.required,
+            text: &line.text,
+            violations,
+            reason,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policy::Registry;
+    use serde_json::Value;
+
+    const DATABASE: &str = "1-3 a: abcde\n\
+                            \n\
+                            1-3 b: cdefg\n\
+                            no colon here\n\
+                            1-x c: ccc\n\
+                            3-1 d: ddd\n\
+                            1-9 e: eeeee\n\
+                            1-2 f: f\u{e9}\n\
+                            1-3 gg: ggg\n";
+
+    fn default_audit() -> Audit {
+        let registry = Registry::default();
+        let policies: Vec<_> = ["count", "positions-xor"]
+            .iter()
+            .map(|&name| (name.to_string(), registry.get(name).unwrap()))
+            .collect();
+        audit(DATABASE, &policies)
+    }
+
+    #[test]
+    fn classifies_every_line_with_its_reason() {
+        let audit = default_audit();
+        let found: Vec<_> = audit
+            .lines
+            .iter()
+            .map(|line| {
+                (
+                    line.line,
+                    line.status.name(),
+                    line.required,
+                    line.status.reason(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (1, "valid", Some('a'), String::new()),
+                (
+                    3,
+                    "invalid",
+                    Some('b'),
+                    "count: 'b' appears 0 times, needs 1 to 3; \
+                     positions-xor: neither position 1 nor 3 holds 'b'"
+                        .into()
+                ),
+                (
+                    4,
+                    "malformed",
+                    None,
+                    "No ':' found in source no colon here".into()
+                ),
+                (
+                    5,
+                    "malformed",
+                    Some('c'),
+                    "bad range \"1-x c\": <max> is not a number".into()
+                ),
+                (
+                    6,
+                    "malformed",
+                    Some('d'),
+                    "min 3 is greater than max 1".into()
+                ),
+                (
+                    7,
+                    "malformed",
+                    Some('e'),
+                    "positions-xor: position 9 is out of bounds for a 5-character password".into()
+                ),
+                (
+                    8,
+                    "malformed",
+                    Some('f'),
+                    "non-ASCII character '\u{e9}' at column 9".into()
+                ),
+                (
+                    9,
+                    "malformed",
+                    None,
+                    "expected a single required character in \"1-3 gg\"".into()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn csv_has_a_column_for_each_part() {
+        let csv = default_audit().to_csv();
+        let rows: Vec<_> = csv.lines().collect();
+        assert_eq!(rows[0], "line,status,policy,reason,text");
+        assert_eq!(rows[1], "1,valid,,,1-3 a: abcde");
+        assert_eq!(
+            rows[2],
+            "3,invalid,count;positions-xor,\"count: 'b' appears 0 times, needs 1 to 3; \
+             positions-xor: neither position 1 nor 3 holds 'b'\",1-3 b: cdefg"
+        );
+        assert_eq!(
+            rows[4],
+            "5,malformed,,\"bad range \"\"1-x c\"\": <max> is not a number\",1-x c: ccc"
+        );
+        assert_eq!(rows.len(), 9);
+    }
+
+    #[test]
+    fn summary_totals_per_required_character() {
+        let audit = default_audit();
+        let csv = audit.summary_csv();
+        let rows: Vec<_> = csv.lines().collect();
+        assert_eq!(
+            rows[0],
+            "required,lines,valid,invalid,malformed,invalid_by_policy"
+        );
+        // lines with no readable required character come first
+        assert_eq!(rows[1], ",2,0,0,2,");
+        assert_eq!(rows[2], "a,1,1,0,0,");
+        assert_eq!(rows[3], "b,1,0,1,0,count=1;positions-xor=1");
+    }
+
+    #[test]
+    fn json_matches_the_lines() {
+        let audit = default_audit();
+        let json: Value = serde_json::from_str(&audit.to_json(true)).unwrap();
+        let lines = json["lines"].as_array().unwrap();
+        assert_eq!(lines.len(), audit.lines.len());
+        assert_eq!(lines[0]["status"], "valid");
+        assert!(lines[0].get("violations").is_none() && lines[0].get("reason").is_none());
+        assert_eq!(lines[1]["line"], 3);
+        assert_eq!(lines[1]["violations"][1]["policy"], "positions-xor");
+        assert_eq!(lines[1]["violations"][1]["rule"], "positions-xor");
+        assert_eq!(
+            lines[1]["violations"][1]["detail"],
+            "neither position 1 nor 3 holds 'b'"
+        );
+        assert_eq!(lines[2]["required"], Value::Null);
+        assert_eq!(lines[2]["reason"], "No ':' found in source no colon here");
+        assert_eq!(lines[6]["text"], "1-2 f: f\u{e9}");
+        assert_eq!(json["summary"][2]["invalid_by_policy"]["count"], 1);
+
+        let summary: Value = serde_json::from_str(&audit.to_json(false)).unwrap();
+        assert!(summary.get("lines").is_none());
+        assert_eq!(summary["summary"], json["summary"]);
+    }
+}
@@ -0,0 +1,104 @@
+pub mod audit;
+pub mod policy;
+
+use common::solution::Solution;
+use common::{parse_vec, take_first_number};
+use policy::{OccurrenceRange, Policy, Positional};
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+pub struct Day2;
+
+impl Solution for Day2 {
+    type Input = Vec<PasswordLine>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(src: &str) -> Result<Vec<PasswordLine>, String> {
+        parse_vec(src)
+    }
+
+    fn part1(passwords: &Vec<PasswordLine>) -> usize {
+        count_valid(passwords, &OccurrenceRange)
+    }
+
+    fn part2(passwords: &Vec<PasswordLine>) -> usize {
+        count_valid(passwords, &Positional::Xor)
+    }
+}
+
+pub fn count_valid(passwords: &[PasswordLine], policy: &dyn Policy) -> usize {
+    passwords.iter().filter(|p| policy.check(p).is_ok()).count()
+}
+
+#[derive(Debug)]
+pub struct PasswordLine {
+    password: String,
+    requirement: Requirement,
+}
+
+impl Display for PasswordLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let req = &self.requirement;
+        write!(
+            f,
+            "{}-{} {}: {}",
+            req.min, req.max, req.required, self.password
+        )
+    }
+}
+
+impl FromStr for PasswordLine {
+    type Err = String;
+
+    //<min>-<max> <required>: <password>
+    fn from_str(s: &str) -> Result<PasswordLine, Self::Err> {
+        if let Some((idx, c)) = s.chars().enumerate().find(|(_, c)| !c.is_ascii()) {
+            return Err(format!("non-ASCII character {:?} at column {}", c, idx + 1));
+        }
+        let (requirements, rest) =
+            s.split_at(s.find(':').ok_or(format!("No ':' found in source {}", s))?);
+        let requirement = Requirement::from_str(requirements)?;
+        let password = rest.trim_start_matches(": ").into();
+
+        Ok(PasswordLine {
+            password,
+            requirement,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Requirement {
+    required: char,
+    min: usize,
+    max: usize,
+}
+
+impl FromStr for Requirement {
+    type Err = String;
+
+    //<min>-<max> <required>
+    fn from_str(s: &str) -> Result<Requirement, Self::Err> {
+        let bad_range = |e: &str| format!("bad range {:?}: {}", s, e);
+        let mut src = s.chars().peekable();
+        let min = take_first_number(&mut src).map_err(|_| bad_range("<min> is not a number"))?;
+
+        if src.next() != Some('-') {
+            return Err(bad_range("expected '-' after <min>"));
+        }
+        let max = take_first_number(&mut src).map_err(|_| bad_range("<max> is not a number"))?;
+        if src.next() != Some(' ') {
+            return Err(bad_range("expected ' ' after <max>"));
+        }
+        let required = src.next().ok_or("Expected a final char after <max>")?;
+        if src.next().is_some() {
+            return Err(format!("expected a single required character in {:?}", s));
+        }
+        if min > max {
+            return Err(format!("min {} is greater than max {}", min, max));
+        }
+
+        Ok(Requirement { min, max, required })
+    }
+}
@@ -0,0 +1,105 @@
+use aoc2020_day02::audit::audit;
+use aoc2020_day02::policy::{PolicyFile, Registry};
+use aoc2020_day02::Day2;
+use common::cli::{self, Args, Cli};
+use common::solution::load_raw_input;
+use std::str::FromStr;
+
+const USAGE: &str = "usage:
+  aoc2020-day02
+  aoc2020-day02 check <policy-file> [--input FILE]
+  aoc2020-day02 audit [--policy NAME]... [--policies FILE] [--format csv|json] [--summary]
+                      [--input FILE]
+  aoc2020-day02 policies
+
+`check` lists every password the policy file's check rejects, with the rule it broke.
+`audit` classifies every line of the database as valid, invalid under some policy, or
+malformed, then totals them per required character. It audits against count and
+positions-xor unless given policies, which may come from a policy file.
+`policies` lists the built-in policies a policy file can refer to.";
+
+const CLI: Cli = Cli {
+    usage: USAGE,
+    switches: &["--summary"],
+    options: &[
+        ("--policy", "a name"),
+        ("--policies", "a file"),
+        ("--format", "csv or json"),
+    ],
+};
+
+fn main() {
+    cli::main::<Day2>(2020, 2, &CLI, run);
+}
+
+fn run(command: &str, args: &Args) -> Result<(), String> {
+    match (command, &args.positional[..]) {
+        ("check", [policy_file]) => check(policy_file, args),
+        ("check", _) => Err(args.usage_error("Expected a policy file".into())),
+        ("audit", []) => audit_database(args),
+        ("policies", []) => {
+            for name in Registry::default().names() {
+                println!("{}", name);
+            }
+            Ok(())
+        }
+        _ => Err(args.unknown_command(command)),
+    }
+}
+
+fn check(policy_file: &str, args: &Args) -> Result<(), String> {
+    let policy = read_policy_file(policy_file)?;
+    let passwords = args.load::<Day2>(2020, 2)?;
+
+    let mut failures = 0;
+    for (idx, password) in passwords.iter().enumerate() {
+        if let Err(violation) = policy.check.check(password) {
+            failures += 1;
+            println!("{}: {} broke {}", idx + 1, password, violation);
+        }
+    }
+    println!(
+        "{} of {} passwords pass",
+        passwords.len() - failures,
+        passwords.len()
+    );
+    Ok(())
+}
+
+fn audit_database(args: &Args) -> Result<(), String> {
+    let registry = match args.value("--policies") {
+        Some(file_name) => read_policy_file(file_name)?.registry,
+        None => Registry::default(),
+    };
+    let json = args.choice("--format", &["csv", "json"])? == Some("json");
+    let names = match args.values("--policy") {
+        names if names.is_empty() => vec!["count", "positions-xor"],
+        names => names,
+    };
+    let policies = names
+        .iter()
+        .map(|&name| match registry.get(name) {
+            Some(policy) => Ok((name.to_string(), policy)),
+            None => Err(format!("Unknown policy: {}", name)),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let src = match args.input() {
+        Some(file_name) => {
+            std::fs::read_to_string(file_name).map_err(|e| format!("{}: {}", file_name, e))?
+        }
+        None => load_raw_input(2020, 2)?,
+    };
+    let audit = audit(&src, &policies);
+    match (json, args.switch("--summary")) {
+        (true, summary) => print!("{}", audit.to_json(!summary)),
+        (false, true) => print!("{}", audit.summary_csv()),
+        (false, false) => print!("{}", audit.to_csv()),
+    }
+    Ok(())
+}
+
+fn read_policy_file(file_name: &str) -> Result<PolicyFile, String> {
+    let src = std::fs::read_to_string(file_name).map_err(|e| format!("{}: {}", file_name, e))?;
+    PolicyFile::from_str(&src).map_err(|e| format!("{}: {}", file_name, e))
+}
@@ -0,0 +1,679 @@
+use crate::PasswordLine;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{self, Display};
+use std::rc::Rc;
+use std::str::FromStr;
+
+/// The rule a password broke, and how.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    pub rule: String,
+    pub detail: String,
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.rule, self.detail)
+    }
+}
+
+pub trait Policy {
+    fn check(&self, line: &PasswordLine) -> Result<(), Violation>;
+
+    // whether the line makes sense under this policy at all, e.g. names positions that exist
+    fn well_formed(&self, _line: &PasswordLine) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+fn violation(rule: &str, detail: String) -> Result<(), Violation> {
+    Err(Violation {
+        rule: rule.into(),
+        detail,
+    })
+}
+
+// the line's own requirement, read as a count: part 1
+pub struct OccurrenceRange;
+
+impl Policy for OccurrenceRange {
+    fn check(&self, line: &PasswordLine) -> Result<(), Violation> {
+        let req = &line.requirement;
+        let total = line.password.chars().filter(|&c| c == req.required).count();
+        if total >= req.min && total <= req.max {
+            Ok(())
+        } else {
+            violation(
+                "count",
+                format!(
+                    "{:?} appears {} times, needs {} to {}",
+                    req.required, total, req.min, req.max
+                ),
+            )
+        }
+    }
+}
+
+// the line's own requirement, read as two 1-based positions; XOR is part 2
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Positional {
+    Xor,
+    And,
+    Or,
+}
+
+impl Policy for Positional {
+    fn well_formed(&self, line: &PasswordLine) -> Result<(), String> {
+        let req = &line.requirement;
+        let len = line.password.chars().count();
+        if req.min == 0 {
+            Err("position 0 is out of bounds; positions start at 1".into())
+        } else if req.max > len {
+            Err(format!(
+                "position {} is out of bounds for a {}-character password",
+                req.max, len
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn check(&self, line: &PasswordLine) -> Result<(), Violation> {
+        let req = &line.requirement;
+        let holds = |pos: usize| {
+            pos.checked_sub(1)
+                .and_then(|idx| line.password.chars().nth(idx))
+                == Some(req.required)
+        };
+        let (first, second) = (holds(req.min), holds(req.max));
+        let (rule, ok) = match self {
+            Positional::Xor => ("positions-xor", first ^ second),
+            Positional::And => ("positions-and", first && second),
+            Positional::Or => ("positions-or", first || second),
+        };
+        if ok {
+            return Ok(());
+        }
+        let detail = match (first, second) {
+            (true, true) => format!(
+                "positions {} and {} both hold {:?}",
+                req.min, req.max, req.required
+            ),
+            (false, false) => format!(
+                "neither position {} nor {} holds {:?}",
+                req.min, req.max, req.required
+            ),
+            (true, false) => format!("position {} doesn't hold {:?}", req.max, req.required),
+            (false, true) => format!("position {} doesn't hold {:?}", req.min, req.required),
+        };
+        violation(rule, detail)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CharClass {
+    Lower,
+    Upper,
+    Digit,
+    Symbol,
+}
+
+impl CharClass {
+    fn contains(self, c: char) -> bool {
+        match self {
+            CharClass::Lower => c.is_lowercase(),
+            CharClass::Upper => c.is_uppercase(),
+            CharClass::Digit => c.is_ascii_digit(),
+            CharClass::Symbol => !c.is_alphanumeric() && !c.is_whitespace(),
+        }
+    }
+}
+
+impl FromStr for CharClass {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "lower" => Ok(CharClass::Lower),
+            "upper" => Ok(CharClass::Upper),
+            "digit" => Ok(CharClass::Digit),
+            "symbol" => Ok(CharClass::Symbol),
+            _ => Err(format!(
+                "Unknown character class {} (expected lower, upper, digit or symbol)",
+                s
+            )),
+        }
+    }
+}
+
+impl Display for CharClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            CharClass::Lower => "lower",
+            CharClass::Upper => "upper",
+            CharClass::Digit => "digit",
+            CharClass::Symbol => "symbol",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// at least one character from each class
+pub struct CharClasses(pub Vec<CharClass>);
+
+impl Policy for CharClasses {
+    fn check(&self, line: &PasswordLine) -> Result<(), Violation> {
+        let missing: Vec<_> = self
+            .0
+            .iter()
+            .filter(|class| !line.password.chars().any(|c| class.contains(c)))
+            .map(|class| class.to_string())
+            .collect();
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            violation("classes", format!("no {} characters", missing.join(" or ")))
+        }
+    }
+}
+
+// Shannon entropy of the password's own character frequencies, times its length
+pub struct MinEntropy(pub f64);
+
+impl MinEntropy {
+    pub fn bits(password: &str) -> f64 {
+        let mut counts: HashMap<char, usize> = HashMap::new();
+        for c in password.chars() {
+            *counts.entry(c).or_default() += 1;
+        }
+        let len = password.chars().count() as f64;
+        let per_char: f64 = counts
+            .values()
+            .map(|&count| {
+                let p = count as f64 / len;
+                p * (1.0 / p).log2()
+            })
+            .sum();
+        per_char * len
+    }
+}
+
+impl Policy for MinEntropy {
+    fn check(&self, line: &PasswordLine) -> Result<(), Violation> {
+        let bits = MinEntropy::bits(&line.password);
+        if bits >= self.0 {
+            Ok(())
+        } else {
+            violation(
+                "entropy",
+                format!("{:.1} bits of entropy, needs {}", bits, self.0),
+            )
+        }
+    }
+}
+
+pub struct ForbiddenSubstrings(pub Vec<String>);
+
+impl Policy for ForbiddenSubstrings {
+    fn check(&self, line: &PasswordLine) -> Result<(), Violation> {
+        match self.0.iter().find(|s| line.password.contains(&s[..])) {
+            Some(s) => violation("forbid", format!("contains {:?}", s)),
+            None => Ok(()),
+        }
+    }
+}
+
+// reports the first rule that fails
+pub struct AllOf(pub Vec<Rc<dyn Policy>>);
+
+impl Policy for AllOf {
+    fn check(&self, line: &PasswordLine) -> Result<(), Violation> {
+        self.0.iter().try_for_each(|policy| policy.check(line))
+    }
+
+    fn well_formed(&self, line: &PasswordLine) -> Result<(), String> {
+        self.0
+            .iter()
+            .try_for_each(|policy| policy.well_formed(line))
+    }
+}
+
+// reports every rule, since all of them failed
+pub struct AnyOf(pub Vec<Rc<dyn Policy>>);
+
+impl Policy for AnyOf {
+    fn check(&self, line: &PasswordLine) -> Result<(), Violation> {
+        let mut failures = vec![];
+        for policy in self.0.iter() {
+            match policy.check(line) {
+                Ok(()) => return Ok(()),
+                Err(v) => failures.push(v.to_string()),
+            }
+        }
+        violation("or", format!("none held ({})", failures.join("; ")))
+    }
+
+    fn well_formed(&self, line: &PasswordLine) -> Result<(), String> {
+        self.0
+            .iter()
+            .try_for_each(|policy| policy.well_formed(line))
+    }
+}
+
+pub struct Not {
+    pub name: String,
+    pub policy: Rc<dyn Policy>,
+}
+
+impl Policy for Not {
+    fn check(&self, line: &PasswordLine) -> Result<(), Violation> {
+        match self.policy.check(line) {
+            Ok(()) => violation("not", format!("{} held", self.name)),
+            Err(_) => Ok(()),
+        }
+    }
+
+    fn well_formed(&self, line: &PasswordLine) -> Result<(), String> {
+        self.policy.well_formed(line)
+    }
+}
+
+// a policy reported under the name it was defined with, followed by the rule inside it
+// that broke, e.g. `strict: count: ...`
+pub struct Named {
+    pub name: String,
+    pub policy: Rc<dyn Policy>,
+}
+
+impl Policy for Named {
+    fn check(&self, line: &PasswordLine) -> Result<(), Violation> {
+        self.policy.check(line).map_err(|v| {
+            if v.rule == self.name {
+                v
+            } else {
+                Violation {
+                    rule: self.name.clone(),
+                    detail: v.to_string(),
+                }
+            }
+        })
+    }
+
+    fn well_formed(&self, line: &PasswordLine) -> Result<(), String> {
+        self.policy.well_formed(line)
+    }
+}
+
+/// Policies by name; starts out with the ones that only read each line's own requirement.
+pub struct Registry {
+    policies: BTreeMap<String, Rc<dyn Policy>>,
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        let mut registry = Registry {
+            policies: BTreeMap::new(),
+        };
+        registry.define("count", Rc::new(OccurrenceRange));
+        registry.define("positions-xor", Rc::new(Positional::Xor));
+        registry.define("positions-and", Rc::new(Positional::And));
+        registry.define("positions-or", Rc::new(Positional::Or));
+        registry
+    }
+}
+
+impl Registry {
+    pub fn define(&mut self, name: &str, policy: Rc<dyn Policy>) {
+        let named = Named {
+            name: name.into(),
+            policy,
+        };
+        self.policies.insert(name.into(), Rc::new(named));
+    }
+
+    pub fn get(&self, name: &str) -> Option<Rc<dyn Policy>> {
+        self.policies.get(name).cloned()
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.policies.keys().map(|name| &name[..])
+    }
+}
+
+/// A policy file: `<name> = <definition>` lines add to the registry, and a single
+/// `check <expression>` line picks what every password must satisfy. A definition is either
+/// one of
+///
+///   classes <lower|upper|digit|symbol>...
+///   entropy <bits>
+///   forbid <substring>...
+///
+/// or an expression combining names with `and`, `or`, `not` and parentheses.
+pub struct PolicyFile {
+    pub registry: Registry,
+    pub check: Rc<dyn Policy>,
+}
+
+impl FromStr for PolicyFile {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut registry = Registry::default();
+        let mut check = None;
+        for (idx, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let in_line = |e: String| format!("line {}: {}", idx + 1, e);
+
+            if let Some(expression) = line.strip_prefix("check ") {
+                if check.is_some() {
+                    return Err(in_line("more than one check line".into()));
+                }
+                check = Some(parse_expression(expression, &registry).map_err(in_line)?);
+            } else if let Some((name, definition)) = line.split_once('=') {
+                let name = name.trim();
+                if name.is_empty() || name.contains(char::is_whitespace) {
+                    return Err(in_line(format!("invalid policy name {:?}", name)));
+                }
+                let policy = parse_definition(definition.trim(), &registry).map_err(in_line)?;
+                registry.define(name, policy);
+            } else {
+                return Err(in_line(
+                    "expected <name> = <definition> or check <expression>".into(),
+                ));
+            }
+        }
+
+        let check = check.ok_or("The policy file has no check line")?;
+        Ok(PolicyFile { registry, check })
+    }
+}
+
+fn parse_definition(definition: &str, registry: &Registry) -> Result<Rc<dyn Policy>, String> {
+    let mut words = definition.split_whitespace();
+    let args: Vec<_> = words.clone().skip(1).collect();
+    let policy: Rc<dyn Policy> = match words.next() {
+        Some("classes") if !args.is_empty() => Rc::new(CharClasses(
+            args.iter()
+                .map(|class| CharClass::from_str(class))
+                .collect::<Result<_, _>>()?,
+        )),
+        Some("entropy") if args.len() == 1 => Rc::new(MinEntropy(
+            f64::from_str(args[0]).map_err(|_| format!("invalid entropy {}", args[0]))?,
+        )),
+        Some("forbid") if !args.is_empty() => Rc::new(ForbiddenSubstrings(
+            args.iter().map(|s| s.to_string()).collect(),
+        )),
+        Some(kind @ ("classes" | "entropy" | "forbid")) => {
+            return Err(format!("wrong number of arguments for {}", kind))
+        }
+        _ => parse_expression(definition, registry)?,
+    };
+    Ok(policy)
+}
+
+pub fn parse_expression(expression: &str, registry: &Registry) -> Result<Rc<dyn Policy>, String> {
+    let spaced = expression.replace('(', " ( ").replace(')', " ) ");
+    let tokens: Vec<_> = spaced.split_whitespace().collect();
+    let mut parser = ExpressionParser {
+        tokens: &tokens,
+        pos: 0,
+        registry,
+    };
+    let policy = parser.or()?;
+    match parser.tokens.get(parser.pos) {
+        None => Ok(policy),
+        Some(token) => Err(format!("unexpected {:?} in {:?}", token, expression)),
+    }
+}
+
+// or binds loosest, then and, then not
+struct ExpressionParser<'a> {
+    tokens: &'a [&'a str],
+    pos: usize,
+    registry: &'a Registry,
+}
+
+impl ExpressionParser<'_> {
+    fn or(&mut self) -> Result<Rc<dyn Policy>, String> {
+        let mut terms = vec![self.and()?];
+        while self.eat("or") {
+            terms.push(self.and()?);
+        }
+        Ok(if terms.len() == 1 {
+            terms.pop().unwrap()
+        } else {
+            Rc::new(AnyOf(terms))
+        })
+    }
+
+    fn and(&mut self) -> Result<Rc<dyn Policy>, String> {
+        let mut terms = vec![self.unary()?];
+        while self.eat("and") {
+            terms.push(self.unary()?);
+        }
+        Ok(if terms.len() == 1 {
+            terms.pop().unwrap()
+        } else {
+            Rc::new(AllOf(terms))
+        })
+    }
+
+    fn unary(&mut self) -> Result<Rc<dyn Policy>, String> {
+        let start = self.pos;
+        if self.eat("not") {
+            let policy = self.unary()?;
+            let name = self.tokens[start + 1..self.pos].join(" ");
+            return Ok(Rc::new(Not { name, policy }));
+        }
+        if self.eat("(") {
+            let policy = self.or()?;
+            if !self.eat(")") {
+                return Err("missing ')'".into());
+            }
+            return Ok(policy);
+        }
+        match self.tokens.get(self.pos) {
+            Some(&name) if !["and", "or", ")"].contains(&name) => {
+                self.pos += 1;
+                self.registry
+                    .get(name)
+                    .ok_or(format!("no policy named {:?}", name))
+            }
+            Some(token) => Err(format!("expected a policy name, found {:?}", token)),
+            None => Err("expected a policy name, found the end of the expression".into()),
+        }
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        if self.tokens.get(self.pos) == Some(&token) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(src: &str) -> PasswordLine {
+        PasswordLine::from_str(src).unwrap()
+    }
+
+    fn check(policy: &dyn Policy, src: &str) -> Result<(), String> {
+        policy.check(&line(src)).map_err(|v| v.to_string())
+    }
+
+    fn policy_file(src: &str) -> Result<PolicyFile, String> {
+        PolicyFile::from_str(src)
+    }
+
+    #[test]
+    fn count_policy() {
+        let count = Registry::default().get("count").unwrap();
+        assert_eq!(check(&*count, "1-3 a: abcde"), Ok(()));
+        assert_eq!(check(&*count, "2-9 c: ccccccccc"), Ok(()));
+        assert_eq!(
+            check(&*count, "1-3 b: cdefg"),
+            Err("count: 'b' appears 0 times, needs 1 to 3".into())
+        );
+        assert_eq!(
+            check(&*count, "1-2 a: aaa"),
+            Err("count: 'a' appears 3 times, needs 1 to 2".into())
+        );
+    }
+
+    #[test]
+    fn positional_policies() {
+        let registry = Registry::default();
+        let cases = [
+            // password, then whether xor, and, or hold
+            ("1-3 a: abcde", [true, false, true]),
+            ("1-3 b: cdefg", [false, false, false]),
+            ("2-9 c: ccccccccc", [false, true, true]),
+            ("1-3 a: aba", [false, true, true]),
+        ];
+        for (src, expected) in cases.iter() {
+            for (name, &holds) in ["positions-xor", "positions-and", "positions-or"]
+                .iter()
+                .zip(expected)
+            {
+                let policy = registry.get(name).unwrap();
+                assert_eq!(check(&*policy, src).is_ok(), holds, "{} on {}", name, src);
+            }
+        }
+        let xor = registry.get("positions-xor").unwrap();
+        assert_eq!(
+            check(&*xor, "2-9 c: ccccccccc"),
+            Err("positions-xor: positions 2 and 9 both hold 'c'".into())
+        );
+        assert_eq!(
+            check(&*xor, "1-3 b: cdefg"),
+            Err("positions-xor: neither position 1 nor 3 holds 'b'".into())
+        );
+        let and = registry.get("positions-and").unwrap();
+        assert_eq!(
+            check(&*and, "1-3 a: abcde"),
+            Err("positions-and: position 3 doesn't hold 'a'".into())
+        );
+    }
+
+    #[test]
+    fn defined_policies() {
+        let file = policy_file(
+            "# every kind of definition\n\
+             mixed = classes lower digit\n\
+             rich = entropy 3\n\
+             plain = forbid password 1234\n\
+             strict = mixed and plain and not positions-and\n\
+             lenient = count or (rich and mixed)\n\
+             check strict\n",
+        )
+        .unwrap();
+        let get = |name| file.registry.get(name).unwrap();
+
+        assert_eq!(check(&*get("mixed"), "1-1 a: abc1"), Ok(()));
+        assert_eq!(
+            check(&*get("mixed"), "1-1 a: ABC!"),
+            Err("mixed: classes: no lower or digit characters".into())
+        );
+        assert_eq!(check(&*get("rich"), "1-1 a: abcd"), Ok(()));
+        assert_eq!(
+            check(&*get("rich"), "1-1 a: aaaa"),
+            Err("rich: entropy: 0.0 bits of entropy, needs 3".into())
+        );
+        assert_eq!(check(&*get("plain"), "1-1 a: hunter2"), Ok(()));
+        assert_eq!(
+            check(&*get("plain"), "1-1 a: mypassword"),
+            Err("plain: forbid: contains \"password\"".into())
+        );
+
+        // and reports the first rule to fail; not fails when the rule inside it holds
+        assert_eq!(check(&*file.check, "1-2 x: ab1cd"), Ok(()));
+        assert_eq!(
+            check(&*file.check, "1-2 x: ab1234"),
+            Err("strict: plain: forbid: contains \"1234\"".into())
+        );
+        assert_eq!(
+            check(&*file.check, "1-2 a: aa1"),
+            Err("strict: not: positions-and held".into())
+        );
+        // or only fails when everything does, and says why each did
+        assert_eq!(check(&*get("lenient"), "1-1 z: za"), Ok(()));
+        assert_eq!(check(&*get("lenient"), "1-1 z: ab12"), Ok(()));
+        assert_eq!(
+            check(&*get("lenient"), "1-1 z: aa"),
+            Err(
+                "lenient: or: none held (count: 'z' appears 0 times, needs 1 to 1; \
+                 rich: entropy: 0.0 bits of entropy, needs 3)"
+                    .into()
+            )
+        );
+    }
+
+    #[test]
+    fn entropy_bits() {
+        assert_eq!(MinEntropy::bits(""), 0.0);
+        assert_eq!(MinEntropy::bits("aaaa"), 0.0);
+        assert_eq!(MinEntropy::bits("ab"), 2.0);
+        assert_eq!(MinEntropy::bits("abcd"), 8.0);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let error = |src| policy_file(src).err().unwrap();
+        assert_eq!(error("x = count\n"), "The policy file has no check line");
+        assert_eq!(
+            error("check count\ncheck count"),
+            "line 2: more than one check line"
+        );
+        assert_eq!(
+            error("check count\nnonsense"),
+            "line 2: expected <name> = <definition> or check <expression>"
+        );
+        assert_eq!(
+            error("two words = count"),
+            "line 1: invalid policy name \"two words\""
+        );
+        assert_eq!(error(" = count"), "line 1: invalid policy name \"\"");
+        assert_eq!(
+            error("x = classes lower vowels"),
+            "line 1: Unknown character class vowels (expected lower, upper, digit or symbol)"
+        );
+        assert_eq!(error("x = entropy lots"), "line 1: invalid entropy lots");
+        assert_eq!(
+            error("x = entropy 1 2"),
+            "line 1: wrong number of arguments for entropy"
+        );
+        assert_eq!(
+            error("x = forbid"),
+            "line 1: wrong number of arguments for forbid"
+        );
+        assert_eq!(
+            error("x = classes"),
+            "line 1: wrong number of arguments for classes"
+        );
+        assert_eq!(
+            error("check missing"),
+            "line 1: no policy named \"missing\""
+        );
+        assert_eq!(error("check (count"), "line 1: missing ')'");
+        assert_eq!(
+            error("check count and"),
+            "line 1: expected a policy name, found the end of the expression"
+        );
+        assert_eq!(
+            error("check count count"),
+            "line 1: unexpected \"count\" in \"count count\""
+        );
+        assert_eq!(
+            error("check or count"),
+            "line 1: expected a policy name, found \"or\""
+        );
+    }
+}
@@ -0,0 +1,296 @@
+use crate::{Slope, TreeLine};
+
+// columns in a drawing of the path, beyond which it's no use to anyone
+const MAX_RENDER_WIDTH: i64 = 1 << 16;
+
+/// The whole forest packed into one bitset, row after row, so that walking many slopes
+/// at once touches each row only once.
+pub struct Forest {
+    bits: Vec<u64>,
+    width: usize,
+    words_per_row: usize,
+    height: usize,
+}
+
+/// The slopes that hit the fewest and the most trees, with every slope tied for each.
+pub struct SearchResult {
+    pub fewest: (usize, Vec<Slope>),
+    pub most: (usize, Vec<Slope>),
+}
+
+impl Forest {
+    pub fn new(lines: &[TreeLine]) -> Result<Forest, String> {
+        let width = lines.first().ok_or("The forest has no rows")?.len();
+        if let Some(idx) = lines.iter().position(|line| line.len() != width) {
+            return Err(format!(
+                "Row {} is {} wide, but the first row is {} wide",
+                idx + 1,
+                lines[idx].len(),
+                width
+            ));
+        }
+        let bits = lines
+            .iter()
+            .flat_map(|line| line.words())
+            .copied()
+            .collect();
+        Ok(Forest {
+            bits,
+            width,
+            words_per_row: width.div_ceil(64),
+            height: lines.len(),
+        })
+    }
+
+    pub fn collisions(&self, slope: Slope) -> usize {
+        self.collisions_many(&[slope])[0]
+    }
+
+    // Walks all the slopes down the forest together. Each slope's column is kept
+    // reduced to the first copy of the map, so every step is an add and a compare
+    // rather than a division, and trees are counted without branching.
+    pub fn collisions_many(&self, slopes: &[Slope]) -> Vec<usize> {
+        let steps: Vec<_> = slopes
+            .iter()
+            .map(|slope| slope.dx.rem_euclid(self.width as i64) as usize)
+            .collect();
+        let mut columns = vec![0; slopes.len()];
+        let mut counts = vec![0; slopes.len()];
+        for y in 0..self.height {
+            let row = &self.bits[y * self.words_per_row..(y + 1) * self.words_per_row];
+            for (idx, slope) in slopes.iter().enumerate() {
+                if y % slope.dy != 0 {
+                    continue;
+                }
+                let column = columns[idx];
+                counts[idx] += ((row[column / 64] >> (column % 64)) & 1) as usize;
+                columns[idx] += steps[idx];
+                if columns[idx] >= self.width {
+                    columns[idx] -= self.width;
+                }
+            }
+        }
+        counts
+    }
+
+    // every slope with -max_dx <= dx <= max_dx and 1 <= dy <= max_dy
+    pub fn search(&self, max_dx: i64, max_dy: usize) -> Option<SearchResult> {
+        let slopes: Vec<_> = (1..=max_dy)
+            .flat_map(|dy| (-max_dx..=max_dx).map(move |dx| Slope { dx, dy }))
+            .collect();
+        let counts = self.collisions_many(&slopes);
+        let fewest = counts.iter().copied().min()?;
+        let most = counts.iter().copied().max()?;
+        let slopes_hitting = |target: usize| {
+            slopes
+                .iter()
+                .zip(&counts)
+                .filter(|&(_, &count)| count == target)
+                .map(|(&slope, _)| slope)
+                .collect()
+        };
+        Some(SearchResult {
+            fewest: (fewest, slopes_hitting(fewest)),
+            most: (most, slopes_hitting(most)),
+        })
+    }
+
+    // The map repeated sideways as far as the path goes, like the puzzle's illustration:
+    // each square the toboggan lands on is an O if it's open, or an X if it's a tree.
+    // Fails if that would be too wide to draw.
+    pub fn render(&self, slope: Slope) -> Result<String, String> {
+        let too_wide = || format!("The path on {} goes too far sideways to draw", slope);
+        let last_step = ((self.height - 1) / slope.dy) as i64;
+        let width = self.width as i64;
+        let last_x = last_step.checked_mul(slope.dx).ok_or_else(too_wide)?;
+        let (first_x, last_x) = if slope.dx < 0 {
+            (last_x, 0)
+        } else {
+            (0, last_x)
+        };
+        let (first_tile, last_tile) = (first_x.div_euclid(width), last_x.div_euclid(width));
+        if (last_tile - first_tile + 1).saturating_mul(width) > MAX_RENDER_WIDTH {
+            return Err(too_wide());
+        }
+
+        let mut out = String::new();
+        for y in 0..self.height {
+            let landing = if y % slope.dy == 0 {
+                Some((y / slope.dy) as i64 * slope.dx)
+            } else {
+                None
+            };
+            for x in first_tile * width..(last_tile + 1) * width {
+                let column = x.rem_euclid(width) as usize;
+                let word = self.bits[y * self.words_per_row + column / 64];
+                let tree = word & (1 << (column % 64)) != 0;
+                out.push(match (landing == Some(x), tree) {
+                    (true, true) => 'X',
+                    (true, false) => 'O',
+                    (false, true) => '#',
+                    (false, false) => '.',
+                });
+            }
+            out.push('\n');
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::count_collisions_on_slope;
+    use crate::tests::EXAMPLE;
+    use std::str::FromStr;
+
+    fn lines(src: &str) -> Vec<TreeLine> {
+        src.lines()
+            .map(|line| TreeLine::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn agrees_with_the_line_by_line_count() {
+        let lines = lines(EXAMPLE);
+        let forest = Forest::new(&lines).unwrap();
+        for dy in 1..=12 {
+            for dx in -25..=25 {
+                let slope = Slope { dx, dy };
+                assert_eq!(
+                    forest.collisions(slope),
+                    count_collisions_on_slope(&lines, slope),
+                    "{}",
+                    slope
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn searches_for_the_best_and_worst_slopes() {
+        let forest = Forest::new(&lines(EXAMPLE)).unwrap();
+        let result = forest.search(3, 2).unwrap();
+
+        let mut counts = vec![];
+        for dy in 1..=2 {
+            for dx in -3..=3 {
+                counts.push(forest.collisions(Slope { dx, dy }));
+            }
+        }
+        let (fewest, most) = (*counts.iter().min().unwrap(), *counts.iter().max().unwrap());
+        assert_eq!(result.fewest.0, fewest);
+        assert_eq!(result.most.0, most);
+        for (count, slopes) in [result.fewest, result.most] {
+            assert!(!slopes.is_empty());
+            assert!(slopes
+                .iter()
+                .all(|&slope| forest.collisions(slope) == count));
+        }
+        assert_eq!(
+            forest.search(3, 1).unwrap().most,
+            (7, vec![Slope { dx: 3, dy: 1 }])
+        );
+    }
+
+    #[test]
+    fn ties_are_all_reported() {
+        // every slope through an empty forest hits nothing, and through a full one hits a
+        // tree on every row it lands on
+        let empty = Forest::new(&lines("...\n...\n...")).unwrap();
+        let result = empty.search(1, 1).unwrap();
+        assert_eq!(result.fewest.0, 0);
+        assert_eq!(result.fewest.1.len(), 3);
+        assert_eq!(result.most, result.fewest);
+
+        let full = Forest::new(&lines("##\n##\n##\n##")).unwrap();
+        let result = full.search(0, 3).unwrap();
+        let fewest = vec![Slope { dx: 0, dy: 2 }, Slope { dx: 0, dy: 3 }];
+        assert_eq!(result.fewest, (2, fewest));
+        assert_eq!(result.most, (4, vec![Slope { dx: 0, dy: 1 }]));
+        assert!(full.search(-1, 1).is_none());
+    }
+
+    // 70 columns, so each row takes two words, with trees either side of the boundary
+    fn wide_row(trees: &[usize]) -> String {
+        (0..70)
+            .map(|column| if trees.contains(&column) { '#' } else { '.' })
+            .collect()
+    }
+
+    #[test]
+    fn rows_wider_than_a_word() {
+        let line = TreeLine::from_str(&wide_row(&[0, 63, 64, 69])).unwrap();
+        assert_eq!(line.len(), 70);
+        assert_eq!(line.tree_count(), 4);
+        for (location, tree) in [(63, true), (64, true), (65, false), (69, true), (70, true)] {
+            assert_eq!(line.is_tree_at(location), tree, "{}", location);
+        }
+        // wrapping around both ways
+        assert!(line.is_tree_at(-1) && line.is_tree_at(-6) && line.is_tree_at(-7));
+        assert!(!line.is_tree_at(-5));
+        assert!(line.is_tree_at(134) && line.is_tree_at(70 * 1000 + 64));
+
+        let rows: Vec<_> = (0..6).map(|y| wide_row(&[(y * 13) % 70, 64])).collect();
+        let lines = lines(&rows.join("\n"));
+        let forest = Forest::new(&lines).unwrap();
+        let slopes: Vec<_> = [(13, 1), (-57, 1), (83, 1), (64, 1), (0, 2), (1, 1)]
+            .iter()
+            .map(|&(dx, dy)| Slope { dx, dy })
+            .collect();
+        let counts = forest.collisions_many(&slopes);
+        assert_eq!(counts, [6, 6, 6, 2, 1, 1]);
+        for (&slope, &count) in slopes.iter().zip(&counts) {
+            assert_eq!(forest.collisions(slope), count);
+            assert_eq!(count_collisions_on_slope(&lines, slope), count);
+        }
+    }
+
+    #[test]
+    fn rejects_ragged_or_empty_forests() {
+        assert_eq!(
+            Forest::new(&lines("..#\n.#\n")).err(),
+            Some("Row 2 is 2 wide, but the first row is 3 wide".into())
+        );
+        assert_eq!(
+            Forest::new(&[]).err(),
+            Some("The forest has no rows".into())
+        );
+    }
+
+    #[test]
+    fn renders_the_path() {
+        let forest = Forest::new(&lines("..#\n#..\n.#.\n..#")).unwrap();
+        assert_eq!(
+            forest.render(Slope { dx: 2, dy: 1 }).unwrap(),
+            "O.#..#..#\n\
+             #.O#..#..\n\
+             .#..X..#.\n\
+             ..#..#O.#\n"
+        );
+        // heading left draws copies to the left; rows the toboggan skips are left alone
+        let forest = Forest::new(&lines("#..\n.#.\n..#")).unwrap();
+        assert_eq!(
+            forest.render(Slope { dx: -1, dy: 1 }).unwrap(),
+            "#..X..\n.#O.#.\n.O#..#\n"
+        );
+        assert_eq!(
+            forest.render(Slope { dx: 1, dy: 2 }).unwrap(),
+            "X..\n.#.\n.O#\n"
+        );
+    }
+
+    #[test]
+    fn refuses_to_render_paths_too_wide_to_draw() {
+        let forest = Forest::new(&lines("#..\n.#.\n..#")).unwrap();
+        for dx in [i64::MAX, i64::MIN, 1 << 20] {
+            assert_eq!(
+                forest.render(Slope { dx, dy: 1 }),
+                Err(format!(
+                    "The path on {},1 goes too far sideways to draw",
+                    dx
+                ))
+            );
+        }
+    }
+}
@@ -0,0 +1,192 @@
+pub mod forest;
+
+use common::parse_vec;
+use common::solution::Solution;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+pub const PUZZLE_SLOPES: [Slope; 5] = [
+    Slope { dx: 1, dy: 1 },
+    Slope { dx: 3, dy: 1 },
+    Slope { dx: 5, dy: 1 },
+    Slope { dx: 7, dy: 1 },
+    Slope { dx: 1, dy: 2 },
+];
+
+pub struct Day3;
+
+impl Solution for Day3 {
+    type Input = Vec<TreeLine>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(src: &str) -> Result<Vec<TreeLine>, String> {
+        parse_vec(src)
+    }
+
+    fn part1(lines: &Vec<TreeLine>) -> usize {
+        lines
+            .iter()
+            .enumerate()
+            .filter(|(i, line)| line.is_tree_at(*i as i64 * 3))
+            .count()
+    }
+
+    fn part2(lines: &Vec<TreeLine>) -> usize {
+        PUZZLE_SLOPES
+            .iter()
+            .map(|&slope| count_collisions_on_slope(lines, slope))
+            .product()
+    }
+}
+
+/// A step of `dx` columns right (left, if negative) and `dy` rows down, written `dx,dy`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Slope {
+    pub dx: i64,
+    pub dy: usize,
+}
+
+impl FromStr for Slope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid slope {:?}: expected <dx>,<dy>", s);
+        let (dx, dy) = s.split_once(',').ok_or_else(invalid)?;
+        let dx = i64::from_str(dx.trim()).map_err(|_| invalid())?;
+        let dy = usize::from_str(dy.trim()).map_err(|_| invalid())?;
+        if dy == 0 {
+            return Err(format!("Invalid slope {:?}: <dy> must be at least 1", s));
+        }
+        Ok(Slope { dx, dy })
+    }
+}
+
+impl Display for Slope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.dx, self.dy)
+    }
+}
+
+pub fn count_collisions_on_slope(lines: &[TreeLine], slope: Slope) -> usize {
+    lines
+        .iter()
+        .step_by(slope.dy)
+        .enumerate()
+        .filter(|(step, line)| line.is_tree_at(*step as i64 * slope.dx))
+        .count()
+}
+
+/// One row of the forest, as a bitset of the columns holding trees.
+#[derive(Debug, Clone)]
+pub struct TreeLine {
+    trees: Vec<u64>,
+    length: usize,
+}
+
+impl TreeLine {
+    // the line repeats endlessly in both directions
+    pub fn is_tree_at(&self, location: i64) -> bool {
+        let column = location.rem_euclid(self.length as i64) as usize;
+        self.trees[column / 64] & (1 << (column % 64)) != 0
+    }
+
+    pub fn tree_count(&self) -> usize {
+        self.trees
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    pub fn len(&self) -> usize {
+        self.length
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    fn words(&self) -> &[u64] {
+        &self.trees
+    }
+}
+
+impl FromStr for TreeLine {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.chars().any(|ch| ch != '#' && ch != '.') {
+            return Err("Line contains characters other than '#' and '.'".into());
+        }
+        if s.is_empty() {
+            return Err("Line is empty".into());
+        }
+
+        let length = s.len();
+        let mut trees = vec![0; length.div_ceil(64)];
+        for (index, _) in s.char_indices().filter(|&(_, ch)| ch == '#') {
+            trees[index / 64] |= 1 << (index % 64);
+        }
+        Ok(TreeLine { trees, length })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    pub(crate) const EXAMPLE: &str = "..##.......\n\
+                               #...#...#..\n\
+                               .#....#..#.\n\
+                               ..#.#...#.#\n\
+                               .#...##..#.\n\
+                               ..#.##.....\n\
+                               .#.#.#....#\n\
+                               .#........#\n\
+                               #.##...#...\n\
+                               #...##....#\n\
+                               .#..#...#.#";
+
+    fn slope(dx: i64, dy: usize) -> Slope {
+        Slope { dx, dy }
+    }
+
+    #[test]
+    fn parses_slopes() {
+        assert_eq!(Slope::from_str("3,1"), Ok(slope(3, 1)));
+        assert_eq!(Slope::from_str(" -2 , 4 "), Ok(slope(-2, 4)));
+        assert_eq!(
+            Slope::from_str("3"),
+            Err("Invalid slope \"3\": expected <dx>,<dy>".into())
+        );
+        assert_eq!(
+            Slope::from_str("3,-1"),
+            Err("Invalid slope \"3,-1\": expected <dx>,<dy>".into())
+        );
+        assert_eq!(
+            Slope::from_str("3,0"),
+            Err("Invalid slope \"3,0\": <dy> must be at least 1".into())
+        );
+        assert_eq!(slope(-1, 2).to_string(), "-1,2");
+    }
+
+    #[test]
+    fn counts_trees_on_any_slope() {
+        let lines = Day3::parse(EXAMPLE).unwrap();
+        let counts: Vec<_> = PUZZLE_SLOPES
+            .iter()
+            .map(|&slope| count_collisions_on_slope(&lines, slope))
+            .collect();
+        assert_eq!(counts, [2, 7, 3, 4, 2]);
+        assert_eq!(Day3::part2(&lines), 336);
+
+        // heading left wraps the same way: -1 steps through columns 0, 10, 9, ...
+        assert_eq!(count_collisions_on_slope(&lines, slope(-1, 1)), 5);
+        // a whole width to either side is the same as going straight down
+        for dx in [-11, 0, 11, 22] {
+            assert_eq!(count_collisions_on_slope(&lines, slope(dx, 1)), 3);
+        }
+        assert_eq!(count_collisions_on_slope(&lines, slope(0, 20)), 0);
+    }
+}
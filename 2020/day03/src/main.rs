@@ -0,0 +1,94 @@
+use aoc2020_day03::forest::Forest;
+use aoc2020_day03::{Day3, Slope};
+use common::cli::{self, Args, Cli};
+use std::str::FromStr;
+
+const USAGE: &str = "usage:
+  aoc2020-day03
+  aoc2020-day03 slopes <dx,dy>... [--input FILE]
+  aoc2020-day03 search [max-dx] [max-dy] [--input FILE]
+  aoc2020-day03 render <dx,dy> [--input FILE]
+
+`slopes` counts the trees hit on each slope, and their product. A negative dx heads left,
+wrapping around the same as heading right. `search` tries every slope with |dx| up to
+max-dx (default 7) and dy from 1 to max-dy (default 2), and reports those hitting the
+fewest and the most trees. `render` draws the map with the path over it: O where the
+toboggan lands on open ground, X where it hits a tree.";
+
+const CLI: Cli = Cli {
+    usage: USAGE,
+    switches: &[],
+    options: &[],
+};
+
+fn main() {
+    cli::main::<Day3>(2020, 3, &CLI, run);
+}
+
+fn run(command: &str, args: &Args) -> Result<(), String> {
+    match command {
+        "slopes" => slopes(args),
+        "search" => search(args),
+        "render" => render(args),
+        _ => Err(args.unknown_command(command)),
+    }
+}
+
+fn slopes(args: &Args) -> Result<(), String> {
+    if args.positional.is_empty() {
+        return Err(args.usage_error("Expected at least one slope".into()));
+    }
+    let slopes = args
+        .positional
+        .iter()
+        .map(|slope| Slope::from_str(slope))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| args.usage_error(e))?;
+    let forest = Forest::new(&args.load::<Day3>(2020, 3)?)?;
+
+    let mut product = Some(1usize);
+    for (slope, count) in slopes.iter().zip(forest.collisions_many(&slopes)) {
+        println!("{}: {}", slope, count);
+        product = product.and_then(|product| product.checked_mul(count));
+    }
+    match product {
+        Some(product) => println!("product: {}", product),
+        None => println!("product: too large to compute"),
+    }
+    Ok(())
+}
+
+fn search(args: &Args) -> Result<(), String> {
+    let (max_dx, max_dy) = match args.positional[..] {
+        [] => (7, 2),
+        [max_dx] => (args.parse_arg(max_dx, "max-dx")?, 2),
+        [max_dx, max_dy] => (
+            args.parse_arg(max_dx, "max-dx")?,
+            args.parse_arg(max_dy, "max-dy")?,
+        ),
+        _ => return Err(args.usage_error("Expected [max-dx] [max-dy]".into())),
+    };
+    if max_dx < 0 || max_dy == 0 {
+        return Err(
+            args.usage_error("max-dx can't be negative, and max-dy must be at least 1".into())
+        );
+    }
+    let forest = Forest::new(&args.load::<Day3>(2020, 3)?)?;
+    let result = forest.search(max_dx, max_dy).unwrap();
+
+    for (label, (count, slopes)) in [("fewest", result.fewest), ("most", result.most)] {
+        let slopes: Vec<_> = slopes.iter().map(|slope| slope.to_string()).collect();
+        println!("{}: {} trees on {}", label, count, slopes.join(" "));
+    }
+    Ok(())
+}
+
+fn render(args: &Args) -> Result<(), String> {
+    let slope = match args.positional[..] {
+        [slope] => Slope::from_str(slope).map_err(|e| args.usage_error(e))?,
+        _ => return Err(args.usage_error("Expected one slope".into())),
+    };
+    let forest = Forest::new(&args.load::<Day3>(2020, 3)?)?;
+    print!("{}", forest.render(slope)?);
+    Ok(())
+}
@@ -0,0 +1,149 @@
+use std::str::FromStr;
+
+/// A record of `key:value` fields separated by whitespace, in the order they appear.
+#[derive(Debug)]
+pub struct Document {
+    tokens: Vec<Token>,
+}
+
+/// One `key:value` field, with the byte offset of its key from the start of the input.
+#[derive(Debug, Clone)]
+pub struct Token {
+    pub key: String,
+    pub value: String,
+    pub offset: usize,
+}
+
+impl Document {
+    // the first value given for `key`
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.tokens
+            .iter()
+            .find(|token| token.key == key)
+            .map(|token| &token.value[..])
+    }
+
+    pub fn tokens(&self) -> &[Token] {
+        &self.tokens
+    }
+}
+
+impl FromStr for Document {
+    type Err = String;
+
+    // a record on its own, so offsets count from its start
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Document::parse_at(s, 0).map_err(|(offset, e)| format!("offset {}: {}", offset, e))
+    }
+}
+
+impl Document {
+    // `start` is where the record begins in the input
+    fn parse_at(s: &str, start: usize) -> Result<Document, (usize, String)> {
+        let tokens = words(s)
+            .map(|(offset, word)| {
+                let offset = start + offset;
+                let (key, value) = word.split_once(':').ok_or((
+                    offset,
+                    format!("expected <key>:<value> but found {:?}", word),
+                ))?;
+                if key.is_empty() {
+                    return Err((offset, format!("{:?} has no key", word)));
+                }
+                Ok(Token {
+                    key: key.into(),
+                    value: value.into(),
+                    offset,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Document { tokens })
+    }
+}
+
+/// Every record in the input, separated by blank lines. Offsets, in the tokens and in
+/// errors, count bytes from the start of the input, and errors give the line too.
+pub fn parse_documents(src: &str) -> Result<Vec<Document>, String> {
+    let mut documents = vec![];
+    let mut start = 0;
+    for record in src.split("\n\n") {
+        let trimmed = record.trim();
+        if !trimmed.is_empty() {
+            let leading = record.len() - record.trim_start().len();
+            let document =
+                Document::parse_at(trimmed, start + leading).map_err(|(offset, e)| {
+                    let line = src[..offset].matches('\n').count() + 1;
+                    format!("line {}, offset {}: {}", line, offset, e)
+                })?;
+            documents.push(document);
+        }
+        start += record.len() + 2;
+    }
+    Ok(documents)
+}
+
+// every run of non-whitespace, with the byte offset it starts at
+fn words(s: &str) -> impl Iterator<Item = (usize, &str)> {
+    let mut rest = s.char_indices().peekable();
+    std::iter::from_fn(move || {
+        while rest.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+        let (start, _) = *rest.peek()?;
+        let mut end = s.len();
+        while let Some(&(idx, c)) = rest.peek() {
+            if c.is_whitespace() {
+                end = idx;
+                break;
+            }
+            rest.next();
+        }
+        Some((start, &s[start..end]))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offsets_count_from_the_start_of_the_input() {
+        let src = "\na:1 b:2\nc:3\n\n\n  d:4\te:\n";
+        let documents = parse_documents(src).unwrap();
+        let tokens: Vec<Vec<_>> = documents
+            .iter()
+            .map(|document| {
+                document
+                    .tokens()
+                    .iter()
+                    .map(|token| (&token.key[..], &token.value[..], token.offset))
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            tokens,
+            [
+                vec![("a", "1", 1), ("b", "2", 5), ("c", "3", 9)],
+                vec![("d", "4", 17), ("e", "", 21)],
+            ]
+        );
+        for (key, _, offset) in tokens.iter().flatten() {
+            assert!(src[*offset..].starts_with(key));
+        }
+    }
+
+    #[test]
+    fn errors_give_the_line_and_offset_in_the_input() {
+        assert_eq!(
+            parse_documents("a:1\nb:2\n\nc:3 oops\n").unwrap_err(),
+            "line 4, offset 13: expected <key>:<value> but found \"oops\""
+        );
+        assert_eq!(
+            parse_documents("a:1\n\nb:2\n\nc:3\n:4").unwrap_err(),
+            "line 6, offset 14: \":4\" has no key"
+        );
+        // a record on its own counts from its start
+        assert_eq!(
+            "a:1 :2".parse::<Document>().unwrap_err(),
+            "offset 4: \":2\" has no key"
+        );
+    }
+}
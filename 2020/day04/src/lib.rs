@@ -0,0 +1,35 @@
+pub mod document;
+pub mod export;
+pub mod schema;
+
+use common::solution::Solution;
+use document::{parse_documents, Document};
+use schema::Schema;
+
+pub struct Day4;
+
+impl Solution for Day4 {
+    type Input = Vec<Document>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(src: &str) -> Result<Vec<Document>, String> {
+        parse_documents(src)
+    }
+
+    fn part1(passports: &Vec<Document>) -> usize {
+        let schema = Schema::default();
+        passports
+            .iter()
+            .filter(|passport| schema.has_required_fields(passport))
+            .count()
+    }
+
+    fn part2(passports: &Vec<Document>) -> usize {
+        let schema = Schema::default();
+        passports
+            .iter()
+            .filter(|passport| schema.is_valid(passport))
+            .count()
+    }
+}
@@ -0,0 +1,95 @@
+use aoc2020_day04::export;
+use aoc2020_day04::schema::{Schema, DEFAULT_SCHEMA};
+use aoc2020_day04::Day4;
+use common::cli::{self, Args, Cli};
+use std::str::FromStr;
+
+const USAGE: &str = "usage:
+  aoc2020-day04
+  aoc2020-day04 validate <schema-file> [--explain] [--input FILE]
+  aoc2020-day04 export [--schema FILE] [--format csv|ndjson] [--input FILE]
+  aoc2020-day04 schema
+
+`validate` checks every document against a schema file, counting those with all the
+required fields and those that are entirely valid; `--explain` lists what's wrong with
+each invalid one, field by field, with byte offsets into the input. `export` writes every
+record in input order, one row each, with its fields, its validity and any failing checks;
+it uses the passport schema unless given another. `schema` prints the passport schema the
+puzzle uses, as a starting point for others.";
+
+const CLI: Cli = Cli {
+    usage: USAGE,
+    switches: &["--explain"],
+    options: &[("--schema", "a file"), ("--format", "csv or ndjson")],
+};
+
+fn main() {
+    cli::main::<Day4>(2020, 4, &CLI, run);
+}
+
+fn run(command: &str, args: &Args) -> Result<(), String> {
+    match (command, &args.positional[..]) {
+        ("validate", [schema_file]) => validate(schema_file, args),
+        ("validate", _) => Err(args.usage_error("Expected a schema file".into())),
+        ("export", []) => export(args),
+        ("schema", []) => {
+            print!("{}", DEFAULT_SCHEMA);
+            Ok(())
+        }
+        _ => Err(args.unknown_command(command)),
+    }
+}
+
+fn validate(schema_file: &str, args: &Args) -> Result<(), String> {
+    let schema = read_schema(schema_file)?;
+    let documents = args.load::<Day4>(2020, 4)?;
+
+    let complete = documents
+        .iter()
+        .filter(|document| schema.has_required_fields(document))
+        .count();
+    let mut valid = 0;
+    for (idx, document) in documents.iter().enumerate() {
+        let failures: Vec<_> = schema
+            .validate(document)
+            .into_iter()
+            .filter(|result| result.problem.is_some())
+            .collect();
+        if failures.is_empty() {
+            valid += 1;
+        } else if args.switch("--explain") {
+            println!("document {}:", idx + 1);
+            for failure in failures {
+                println!("  {}", failure);
+            }
+        }
+    }
+    println!(
+        "{} of {} {} documents have every required field, and {} are valid",
+        complete,
+        documents.len(),
+        schema.name,
+        valid
+    );
+    Ok(())
+}
+
+fn export(args: &Args) -> Result<(), String> {
+    let ndjson = args.choice("--format", &["csv", "ndjson"])? == Some("ndjson");
+    let schema = match args.value("--schema") {
+        Some(schema_file) => read_schema(schema_file)?,
+        None => Schema::default(),
+    };
+    let rows = export::rows(&args.load::<Day4>(2020, 4)?, &schema);
+    if ndjson {
+        print!("{}", export::to_ndjson(&rows));
+    } else {
+        print!("{}", export::to_csv(&rows, &schema));
+    }
+    Ok(())
+}
+
+fn read_schema(file_name: &str) -> Result<Schema, String> {
+    let src = std::fs::read_to_string(file_name).map_err(|e| format!("{}: {}", file_name, e))?;
+    Schema::from_str(&src).map_err(|e| format!("{}: {}", file_name, e))
+}
@@ -0,0 +1,436 @@
+use crate::document::Document;
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+pub const DEFAULT_SCHEMA: &str = include_str!("../schemas/passport.toml");
+
+/// The fields a kind of document has and what each must hold, loaded from a TOML file:
+///
+///   name = "passport"
+///
+///   [fields.hgt]
+///   required = true     # the default
+///   type = "units"      # or range, regex, enum; leave it out to accept anything
+///   units = { cm = [150, 193], in = [59, 76] }
+///
+/// Ranges take `min` and `max`, regexes a `pattern` the whole value must match, and enums
+/// the allowed `values`.
+pub struct Schema {
+    pub name: String,
+    pub fields: BTreeMap<String, Field>,
+}
+
+pub struct Field {
+    pub required: bool,
+    pub rule: Rule,
+}
+
+pub enum Rule {
+    Any,
+    Range { min: i64, max: i64 },
+    // a number followed by one of the units, each with its own range
+    Units(BTreeMap<String, (i64, i64)>),
+    Regex { pattern: String, re: Regex },
+    Enum(Vec<String>),
+}
+
+/// How one field of a document fared: every field it gives gets a result, and so does
+/// every required field it leaves out.
+pub struct FieldResult {
+    pub key: String,
+    // None for a missing field
+    pub offset: Option<usize>,
+    pub problem: Option<Problem>,
+}
+
+pub enum Problem {
+    Missing,
+    // only the first value given for a key is checked
+    Duplicate { first: usize },
+    Unknown,
+    Empty,
+    Invalid(String),
+}
+
+impl Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Problem::Missing => write!(f, "missing"),
+            Problem::Duplicate { first } => write!(f, "duplicate of the field at offset {}", first),
+            Problem::Unknown => write!(f, "not in the schema"),
+            Problem::Empty => write!(f, "empty value"),
+            Problem::Invalid(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+impl Display for FieldResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.key)?;
+        if let Some(offset) = self.offset {
+            write!(f, " (offset {})", offset)?;
+        }
+        match &self.problem {
+            Some(problem) => write!(f, ": {}", problem),
+            None => write!(f, ": ok"),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SchemaFile {
+    name: String,
+    fields: BTreeMap<String, FieldFile>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FieldFile {
+    #[serde(default = "required_by_default")]
+    required: bool,
+    #[serde(rename = "type")]
+    kind: Option<String>,
+    min: Option<i64>,
+    max: Option<i64>,
+    units: Option<BTreeMap<String, (i64, i64)>>,
+    pattern: Option<String>,
+    values: Option<Vec<String>>,
+}
+
+fn required_by_default() -> bool {
+    true
+}
+
+impl Default for Schema {
+    fn default() -> Self {
+        Schema::from_str(DEFAULT_SCHEMA).unwrap()
+    }
+}
+
+impl FromStr for Schema {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let file: SchemaFile = toml::from_str(s).map_err(|e| e.to_string())?;
+        let fields = file
+            .fields
+            .into_iter()
+            .map(|(name, field)| {
+                let field = Field::new(field).map_err(|e| format!("field {}: {}", name, e))?;
+                Ok((name, field))
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(Schema {
+            name: file.name,
+            fields,
+        })
+    }
+}
+
+impl Field {
+    fn new(field: FieldFile) -> Result<Field, String> {
+        let FieldFile {
+            required,
+            kind,
+            min,
+            max,
+            units,
+            pattern,
+            values,
+        } = field;
+        let given = [
+            ("min", min.is_some()),
+            ("max", max.is_some()),
+            ("units", units.is_some()),
+            ("pattern", pattern.is_some()),
+            ("values", values.is_some()),
+        ];
+        let expected: &[&str] = match kind.as_deref() {
+            None => &[],
+            Some("range") => &["min", "max"],
+            Some("units") => &["units"],
+            Some("regex") => &["pattern"],
+            Some("enum") => &["values"],
+            Some(other) => return Err(format!("unknown type {:?}", other)),
+        };
+        for (param, is_given) in given {
+            if is_given != expected.contains(&param) {
+                return Err(match kind {
+                    Some(kind) if is_given => format!("{} doesn't apply to {} fields", param, kind),
+                    Some(kind) => format!("{} fields need {}", kind, param),
+                    None => format!("{} needs a type", param),
+                });
+            }
+        }
+
+        let rule = match kind.as_deref() {
+            None => Rule::Any,
+            Some("range") => Rule::Range {
+                min: min.unwrap(),
+                max: max.unwrap(),
+            },
+            Some("units") => Rule::Units(units.unwrap()),
+            Some("regex") => {
+                let pattern = pattern.unwrap();
+                let re = Regex::new(&format!("^(?:{})$", pattern)).map_err(|e| e.to_string())?;
+                Rule::Regex { pattern, re }
+            }
+            Some(_) => Rule::Enum(values.unwrap()),
+        };
+        Ok(Field { required, rule })
+    }
+}
+
+impl Rule {
+    // why the value breaks the rule, if it does
+    pub fn check(&self, value: &str) -> Result<(), String> {
+        match self {
+            Rule::Any => Ok(()),
+            Rule::Range { min, max } => {
+                let n = parse_integer(value).ok_or(format!("{:?} is not a number", value))?;
+                check_between(n, *min, *max, "")
+            }
+            Rule::Units(units) => {
+                let (n, unit) = units
+                    .keys()
+                    .find_map(|unit| {
+                        let n = value.strip_suffix(&unit[..]).and_then(parse_integer)?;
+                        Some((n, unit))
+                    })
+                    .ok_or_else(|| {
+                        let units: Vec<_> = units.keys().map(|unit| &unit[..]).collect();
+                        format!(
+                            "{:?} is not a number followed by {}",
+                            value,
+                            units.join(" or ")
+                        )
+                    })?;
+                let (min, max) = units[unit];
+                check_between(n, min, max, unit)
+            }
+            Rule::Regex { pattern, re } => {
+                if re.is_match(value) {
+                    Ok(())
+                } else {
+                    Err(format!("{:?} doesn't match {}", value, pattern))
+                }
+            }
+            Rule::Enum(values) => {
+                if values.iter().any(|v| v == value) {
+                    Ok(())
+                } else {
+                    Err(format!("{:?} is not one of {}", value, values.join(", ")))
+                }
+            }
+        }
+    }
+}
+
+fn check_between(n: i64, min: i64, max: i64, unit: &str) -> Result<(), String> {
+    if min <= n && n <= max {
+        Ok(())
+    } else {
+        Err(format!(
+            "{}{} is not between {}{} and {}{}",
+            n, unit, min, unit, max, unit
+        ))
+    }
+}
+
+// digits only, unlike i64::from_str, which also takes a leading '+'
+fn parse_integer(src: &str) -> Option<i64> {
+    let digits = src.strip_prefix('-').unwrap_or(src);
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    i64::from_str(src).ok()
+}
+
+impl Schema {
+    pub fn has_required_fields(&self, document: &Document) -> bool {
+        self.fields
+            .iter()
+            .filter(|(_, field)| field.required)
+            .all(|(name, _)| document.get(name).is_some())
+    }
+
+    // the document's fields in order, then any required fields it's missing
+    pub fn validate(&self, document: &Document) -> Vec<FieldResult> {
+        let mut first_offsets = HashMap::new();
+        let mut results: Vec<_> = document
+            .tokens()
+            .iter()
+            .map(|token| {
+                let problem = if let Some(&first) = first_offsets.get(&token.key[..]) {
+                    Some(Problem::Duplicate { first })
+                } else {
+                    first_offsets.insert(&token.key[..], token.offset);
+                    match self.fields.get(&token.key) {
+                        None => Some(Problem::Unknown),
+                        Some(_) if token.value.is_empty() => Some(Problem::Empty),
+                        Some(field) => field.rule.check(&token.value).err().map(Problem::Invalid),
+                    }
+                };
+                FieldResult {
+                    key: token.key.clone(),
+                    offset: Some(token.offset),
+                    problem,
+                }
+            })
+            .collect();
+
+        results.extend(
+            self.fields
+                .iter()
+                .filter(|(name, field)| field.required && !first_offsets.contains_key(&name[..]))
+                .map(|(name, _)| FieldResult {
+                    key: name.clone(),
+                    offset: None,
+                    problem: Some(Problem::Missing),
+                }),
+        );
+        results
+    }
+
+    pub fn is_valid(&self, document: &Document) -> bool {
+        self.validate(document)
+            .iter()
+            .all(|result| result.problem.is_none())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::parse_documents;
+
+    fn accepts(field: &str, value: &str) -> bool {
+        Schema::default().fields[field].rule.check(value).is_ok()
+    }
+
+    #[test]
+    fn heights_are_bounded_per_unit() {
+        for value in ["150cm", "193cm", "59in", "76in", "60in", "190cm"] {
+            assert!(accepts("hgt", value), "{}", value);
+        }
+        for value in [
+            "149cm", "194cm", "58in", "77in", "190in", "190", "cm", "+60in", "60 in",
+        ] {
+            assert!(!accepts("hgt", value), "{}", value);
+        }
+    }
+
+    #[test]
+    fn hair_colours_match_the_whole_pattern() {
+        for value in ["#123abc", "#000000", "#ffffff"] {
+            assert!(accepts("hcl", value), "{}", value);
+        }
+        for value in [
+            "#123abz", "123abc", "#123ab", "#123abcd", "#123ABC", "x#123abc",
+        ] {
+            assert!(!accepts("hcl", value), "{}", value);
+        }
+    }
+
+    #[test]
+    fn eye_colours_come_from_the_list() {
+        assert!(accepts("ecl", "brn"));
+        for value in ["wat", "br", "brnn", "BRN", ""] {
+            assert!(!accepts("ecl", value), "{}", value);
+        }
+    }
+
+    #[test]
+    fn ranges_and_ids() {
+        assert!(accepts("byr", "2002"));
+        assert!(!accepts("byr", "2003"));
+        assert!(!accepts("byr", "+2000"));
+        assert!(accepts("pid", "000000001"));
+        assert!(!accepts("pid", "0123456789"));
+        assert!(accepts("cid", "anything"));
+    }
+
+    #[test]
+    fn validates_whole_documents() {
+        let schema = Schema::default();
+        let valid: Document =
+            "pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980 hcl:#623a2f"
+                .parse()
+                .unwrap();
+        assert!(schema.has_required_fields(&valid));
+        assert!(schema.is_valid(&valid));
+
+        let bad_ecl: Document =
+            "pid:087499704 hgt:74in ecl:zzz iyr:2012 eyr:2030 byr:1980 hcl:#623a2f"
+                .parse()
+                .unwrap();
+        assert!(schema.has_required_fields(&bad_ecl));
+        assert!(!schema.is_valid(&bad_ecl));
+
+        let missing: Document = "hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980 hcl:#623a2f cid:1"
+            .parse()
+            .unwrap();
+        assert!(!schema.has_required_fields(&missing));
+        assert!(!schema.is_valid(&missing));
+    }
+
+    #[test]
+    fn reports_problems_field_by_field() {
+        let schema = Schema::default();
+        let documents = parse_documents(
+            "pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980 hcl:#623a2f\n\n\
+             hgt:190in ecl:grn zzz:1 ecl:blu\niyr: byr:1980\n",
+        )
+        .unwrap();
+        let report: Vec<_> = documents
+            .iter()
+            .map(|document| {
+                schema
+                    .validate(document)
+                    .iter()
+                    .map(|result| result.to_string())
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        assert!(report[0].iter().all(|line| line.ends_with(": ok")));
+        assert_eq!(
+            report[1],
+            [
+                "hgt (offset 71): 190in is not between 59in and 76in",
+                "ecl (offset 81): ok",
+                "zzz (offset 89): not in the schema",
+                "ecl (offset 95): duplicate of the field at offset 81",
+                "iyr (offset 103): empty value",
+                "byr (offset 108): ok",
+                "eyr: missing",
+                "hcl: missing",
+                "pid: missing",
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_schemas() {
+        let parse = |src: &str| Schema::from_str(src).err();
+        assert_eq!(
+            parse("name = \"x\"\n[fields.a]\ntype = \"range\"\nmin = 1\n"),
+            Some("field a: range fields need max".into())
+        );
+        assert_eq!(
+            parse("name = \"x\"\n[fields.a]\ntype = \"enum\"\nvalues = []\nmin = 1\n"),
+            Some("field a: min doesn't apply to enum fields".into())
+        );
+        assert_eq!(
+            parse("name = \"x\"\n[fields.a]\ntype = \"colour\"\n"),
+            Some("field a: unknown type \"colour\"".into())
+        );
+        assert_eq!(
+            parse("name = \"x\"\n[fields.a]\npattern = \"a\"\n"),
+            Some("field a: pattern needs a type".into())
+        );
+    }
+}
@@ -0,0 +1,51 @@
+pub mod occupancy;
+pub mod pass;
+
+use common::parse_vec;
+use common::solution::{Answer, Solution};
+use occupancy::Occupancy;
+use pass::{BoardingPass, Cabin};
+
+pub struct Day5;
+
+impl Solution for Day5 {
+    type Input = Vec<BoardingPass>;
+    type Part1 = Answer<usize>;
+    type Part2 = Answer<usize>;
+
+    fn parse(src: &str) -> Result<Vec<BoardingPass>, String> {
+        let cabin = Cabin::default();
+        parse_vec::<String, _>(src)?
+            .iter()
+            .map(|code| cabin.decode(code))
+            .collect()
+    }
+
+    // no answer for an empty list
+    fn part1(passes: &Vec<BoardingPass>) -> Answer<usize> {
+        Answer(passes.iter().map(|pass| pass.seat_id()).max())
+    }
+
+    // no answer if every empty seat is at the front or back
+    fn part2(passes: &Vec<BoardingPass>) -> Answer<usize> {
+        let gaps = Occupancy::new(Cabin::default(), passes).empty_between_occupied();
+        Answer(gaps.first().copied())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_answers_without_passes_or_a_gap() {
+        let cabin = Cabin::default();
+        let passes =
+            |ids: &[usize]| -> Vec<_> { ids.iter().map(|&id| cabin.seat(id).unwrap()).collect() };
+        assert_eq!(Day5::part1(&vec![]), Answer(None));
+        assert_eq!(Day5::part2(&vec![]), Answer(None));
+        assert_eq!(Day5::part2(&passes(&[10, 11, 12])), Answer(None));
+        assert_eq!(Day5::part1(&passes(&[10, 12, 11])), Answer(Some(12)));
+        assert_eq!(Day5::part2(&passes(&[10, 12, 14, 13])), Answer(Some(11)));
+    }
+}
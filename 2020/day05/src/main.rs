@@ -0,0 +1,123 @@
+use aoc2020_day05::occupancy::Occupancy;
+use aoc2020_day05::pass::{BoardingPass, Cabin};
+use aoc2020_day05::Day5;
+use common::cli::{self, Args, Cli};
+use common::solution::load_raw_input;
+
+const USAGE: &str = "usage:
+  aoc2020-day05
+  aoc2020-day05 decode <pass>... [--rows N] [--columns N]
+  aoc2020-day05 encode <seat-id>... [--rows N] [--columns N]
+  aoc2020-day05 <map|empty|gaps|duplicates> [--rows N] [--columns N] [--input FILE]
+
+The cabin has 128 rows of 8 seats unless told otherwise; a pass spells out the row in F
+and B, then the column in L and R, with as many of each as it takes to number them.
+
+The rest look at the seats the passes fill: `map` draws the cabin (# taken, . empty,
+! more than one pass), `empty` lists every empty seat, `gaps` the empty seats whose
+neighbours on both sides are taken, and `duplicates` the seats with more than one pass.";
+
+const CLI: Cli = Cli {
+    usage: USAGE,
+    switches: &[],
+    options: &[("--rows", "a number"), ("--columns", "a number")],
+};
+
+fn main() {
+    cli::main::<Day5>(2020, 5, &CLI, run);
+}
+
+fn run(command: &str, args: &Args) -> Result<(), String> {
+    let cabin = cabin(args).map_err(|e| args.usage_error(e))?;
+    match (command, &args.positional[..]) {
+        ("decode" | "encode", []) => Err(args.usage_error(format!("Nothing to {}", command))),
+        ("decode", codes) => {
+            for code in codes {
+                let pass = cabin.decode(code)?;
+                println!(
+                    "{}: row {}, column {}, seat ID {}",
+                    code,
+                    pass.row(),
+                    pass.column(),
+                    pass.seat_id()
+                );
+            }
+            Ok(())
+        }
+        ("encode", seat_ids) => {
+            for seat_id in seat_ids {
+                let code = cabin.encode(args.parse_arg(seat_id, "seat ID")?)?;
+                println!("{}: {}", seat_id, code);
+            }
+            Ok(())
+        }
+        ("map", []) => {
+            print!("{}", occupancy(args, cabin)?.0.render());
+            Ok(())
+        }
+        ("empty", []) => {
+            print_seats(&occupancy(args, cabin)?.0.empty_seats(), cabin);
+            Ok(())
+        }
+        ("gaps", []) => {
+            print_seats(&occupancy(args, cabin)?.0.empty_between_occupied(), cabin);
+            Ok(())
+        }
+        ("duplicates", []) => {
+            let (occupancy, line_numbers) = occupancy(args, cabin)?;
+            for (seat_id, passes) in occupancy.duplicates() {
+                let lines: Vec<_> = passes
+                    .iter()
+                    .map(|&idx| line_numbers[idx].to_string())
+                    .collect();
+                println!("seat {}: passes on lines {}", seat_id, lines.join(", "));
+            }
+            Ok(())
+        }
+        _ => Err(args.unknown_command(command)),
+    }
+}
+
+fn cabin(args: &Args) -> Result<Cabin, String> {
+    let default = Cabin::default();
+    Cabin::new(
+        args.parsed("--rows")?.unwrap_or(default.rows()),
+        args.parsed("--columns")?.unwrap_or(default.columns()),
+    )
+}
+
+// the passes for the cabin, with the line number each came from
+fn occupancy(args: &Args, cabin: Cabin) -> Result<(Occupancy, Vec<usize>), String> {
+    let src = match args.input() {
+        Some(file_name) => {
+            std::fs::read_to_string(file_name).map_err(|e| format!("{}: {}", file_name, e))?
+        }
+        None => load_raw_input(2020, 5)?,
+    };
+    let mut passes: Vec<BoardingPass> = vec![];
+    let mut line_numbers = vec![];
+    for (idx, line) in src.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let pass = cabin
+            .decode(line)
+            .map_err(|e| format!("line {}: {}", idx + 1, e))?;
+        passes.push(pass);
+        line_numbers.push(idx + 1);
+    }
+    Ok((Occupancy::new(cabin, &passes), line_numbers))
+}
+
+fn print_seats(seat_ids: &[usize], cabin: Cabin) {
+    for &seat_id in seat_ids {
+        let pass = cabin.seat(seat_id).unwrap();
+        println!(
+            "seat {}: row {}, column {}",
+            seat_id,
+            pass.row(),
+            pass.column()
+        );
+    }
+}
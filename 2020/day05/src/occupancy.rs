@@ -0,0 +1,152 @@
+use crate::pass::{BoardingPass, Cabin};
+use std::collections::BTreeMap;
+
+/// Which seats in the cabin have a boarding pass, as a bitset over seat IDs.
+pub struct Occupancy {
+    cabin: Cabin,
+    occupied: Vec<u64>,
+    // the indices of every pass for each seat that has more than one
+    duplicates: BTreeMap<usize, Vec<usize>>,
+}
+
+impl Occupancy {
+    // the passes must have been decoded for this cabin
+    pub fn new(cabin: Cabin, passes: &[BoardingPass]) -> Occupancy {
+        let mut occupied = vec![0; cabin.seats().div_ceil(64)];
+        let mut passes_by_seat: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+        for (idx, pass) in passes.iter().enumerate() {
+            let seat_id = pass.seat_id();
+            occupied[seat_id / 64] |= 1 << (seat_id % 64);
+            passes_by_seat.entry(seat_id).or_default().push(idx);
+        }
+        passes_by_seat.retain(|_, passes| passes.len() > 1);
+
+        Occupancy {
+            cabin,
+            occupied,
+            duplicates: passes_by_seat,
+        }
+    }
+
+    pub fn is_occupied(&self, seat_id: usize) -> bool {
+        seat_id < self.cabin.seats() && self.occupied[seat_id / 64] & (1 << (seat_id % 64)) != 0
+    }
+
+    pub fn duplicates(&self) -> &BTreeMap<usize, Vec<usize>> {
+        &self.duplicates
+    }
+
+    pub fn empty_seats(&self) -> Vec<usize> {
+        let empty: Vec<_> = self.occupied.iter().map(|word| !word).collect();
+        self.seats_in(&empty)
+    }
+
+    // empty seats with both the seat IDs either side of them taken
+    pub fn empty_between_occupied(&self) -> Vec<usize> {
+        let words = &self.occupied;
+        let between: Vec<_> = (0..words.len())
+            .map(|idx| {
+                let before = idx.checked_sub(1).map_or(0, |prev| words[prev] >> 63);
+                let after = words.get(idx + 1).map_or(0, |next| next << 63);
+                let left_taken = words[idx] << 1 | before;
+                let right_taken = words[idx] >> 1 | after;
+                !words[idx] & left_taken & right_taken
+            })
+            .collect();
+        self.seats_in(&between)
+    }
+
+    // The cabin a row to a line, front first: '#' for a taken seat, '.' for an empty one,
+    // and '!' for one with more than one pass.
+    pub fn render(&self) -> String {
+        let width = (self.cabin.rows() - 1).to_string().len();
+        let mut out = String::new();
+        for row in 0..self.cabin.rows() {
+            out.push_str(&format!("{:>width$} ", row, width = width));
+            for column in 0..self.cabin.columns() {
+                let seat_id = row * self.cabin.columns() + column;
+                out.push(if self.duplicates.contains_key(&seat_id) {
+                    '!'
+                } else if self.is_occupied(seat_id) {
+                    '#'
+                } else {
+                    '.'
+                });
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    fn seats_in(&self, bits: &[u64]) -> Vec<usize> {
+        let mut seats = vec![];
+        for (idx, &word) in bits.iter().enumerate() {
+            let mut word = word;
+            while word != 0 {
+                let seat_id = idx * 64 + word.trailing_zeros() as usize;
+                if seat_id >= self.cabin.seats() {
+                    break;
+                }
+                seats.push(seat_id);
+                word &= word - 1;
+            }
+        }
+        seats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn occupancy(cabin: Cabin, seat_ids: &[usize]) -> Occupancy {
+        let passes: Vec<_> = seat_ids.iter().map(|&id| cabin.seat(id).unwrap()).collect();
+        Occupancy::new(cabin, &passes)
+    }
+
+    #[test]
+    fn finds_gaps_across_word_boundaries() {
+        let cabin = Cabin::new(20, 10).unwrap();
+        let mut taken: Vec<_> = (0..200).collect();
+        // an isolated gap either side of a word boundary, a gap two seats wide, and the
+        // first and last seats
+        taken.retain(|id| ![0, 63, 64, 100, 101, 128, 199].contains(id));
+        let full = occupancy(cabin, &taken);
+        assert_eq!(full.empty_between_occupied(), [128]);
+        assert_eq!(full.empty_seats(), [0, 63, 64, 100, 101, 128, 199]);
+
+        let sparse = occupancy(cabin, &[62, 64, 127, 129, 198]);
+        assert_eq!(sparse.empty_between_occupied(), [63, 128]);
+        assert!(sparse.is_occupied(198));
+        assert!(!sparse.is_occupied(199));
+        assert!(!sparse.is_occupied(200));
+    }
+
+    #[test]
+    fn no_gaps_without_a_seat_either_side() {
+        let cabin = Cabin::new(4, 4).unwrap();
+        assert!(occupancy(cabin, &[]).empty_between_occupied().is_empty());
+        assert!(occupancy(cabin, &(0..16).collect::<Vec<_>>())
+            .empty_between_occupied()
+            .is_empty());
+        assert!(occupancy(cabin, &[3, 4, 5, 8, 9])
+            .empty_between_occupied()
+            .is_empty());
+        // the seats past the end of the cabin aren't seats
+        assert!(occupancy(cabin, &[14]).empty_between_occupied().is_empty());
+    }
+
+    #[test]
+    fn records_every_pass_for_a_duplicated_seat() {
+        let cabin = Cabin::new(2, 3).unwrap();
+        let occupancy = occupancy(cabin, &[4, 1, 4, 0, 1, 4]);
+        let duplicates: Vec<_> = occupancy
+            .duplicates()
+            .iter()
+            .map(|(&seat, passes)| (seat, passes.clone()))
+            .collect();
+        assert_eq!(duplicates, [(1, vec![1, 4]), (4, vec![0, 2, 5])]);
+        assert_eq!(occupancy.empty_seats(), [2, 3, 5]);
+        assert_eq!(occupancy.render(), "0 #!.\n1 .!.\n");
+    }
+}
@@ -0,0 +1,244 @@
+/// The shape of the cabin. Passes give the row in F/B characters and then the column in
+/// L/R ones, as binary with as many digits as it takes to number every row or column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cabin {
+    rows: usize,
+    columns: usize,
+}
+
+/// A decoded pass; its seat ID is `row * columns + column`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BoardingPass {
+    row: usize,
+    column: usize,
+    seat_id: usize,
+}
+
+impl BoardingPass {
+    pub fn row(&self) -> usize {
+        self.row
+    }
+
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    pub fn seat_id(&self) -> usize {
+        self.seat_id
+    }
+}
+
+impl Default for Cabin {
+    fn default() -> Self {
+        Cabin {
+            rows: 128,
+            columns: 8,
+        }
+    }
+}
+
+impl Cabin {
+    pub fn new(rows: usize, columns: usize) -> Result<Cabin, String> {
+        if rows == 0 || columns == 0 {
+            return Err(format!("A {}x{} cabin has no seats", rows, columns));
+        }
+        Ok(Cabin { rows, columns })
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    pub fn seats(&self) -> usize {
+        self.rows * self.columns
+    }
+
+    pub fn decode(&self, code: &str) -> Result<BoardingPass, String> {
+        let (row_bits, column_bits) = (bits_for(self.rows), bits_for(self.columns));
+        let length = code.chars().count();
+        if length != row_bits + column_bits {
+            return Err(format!(
+                "{:?} is {} characters long, but passes for this cabin have {}",
+                code,
+                length,
+                row_bits + column_bits
+            ));
+        }
+
+        let (mut row, mut column) = (0, 0);
+        for (idx, c) in code.chars().enumerate() {
+            let (value, bit) = if idx < row_bits {
+                (&mut row, digit(c, ['F', 'B']))
+            } else {
+                (&mut column, digit(c, ['L', 'R']))
+            };
+            let bit = bit.ok_or_else(|| {
+                let expected = if idx < row_bits { "F or B" } else { "L or R" };
+                format!(
+                    "{:?} has {:?} at position {}, where it should have {}",
+                    code,
+                    c,
+                    idx + 1,
+                    expected
+                )
+            })?;
+            *value = *value << 1 | bit;
+        }
+
+        if row >= self.rows {
+            return Err(format!(
+                "{:?} is for row {}, but the cabin has {}",
+                code, row, self.rows
+            ));
+        }
+        if column >= self.columns {
+            return Err(format!(
+                "{:?} is for column {}, but the cabin has {}",
+                code, column, self.columns
+            ));
+        }
+        Ok(self.pass(row, column))
+    }
+
+    pub fn encode(&self, seat_id: usize) -> Result<String, String> {
+        let pass = self.seat(seat_id)?;
+        let mut code = String::new();
+        push_bits(&mut code, pass.row, bits_for(self.rows), ['F', 'B']);
+        push_bits(&mut code, pass.column, bits_for(self.columns), ['L', 'R']);
+        Ok(code)
+    }
+
+    pub fn seat(&self, seat_id: usize) -> Result<BoardingPass, String> {
+        if seat_id >= self.seats() {
+            return Err(format!(
+                "Seat {} is past the last seat in the cabin ({})",
+                seat_id,
+                self.seats() - 1
+            ));
+        }
+        Ok(self.pass(seat_id / self.columns, seat_id % self.columns))
+    }
+
+    fn pass(&self, row: usize, column: usize) -> BoardingPass {
+        BoardingPass {
+            row,
+            column,
+            seat_id: row * self.columns + column,
+        }
+    }
+}
+
+// how many binary digits it takes to number `n` things from 0
+fn bits_for(n: usize) -> usize {
+    if n <= 1 {
+        0
+    } else {
+        (n - 1).ilog2() as usize + 1
+    }
+}
+
+fn digit(c: char, [zero, one]: [char; 2]) -> Option<usize> {
+    match c {
+        _ if c == zero => Some(0),
+        _ if c == one => Some(1),
+        _ => None,
+    }
+}
+
+fn push_bits(code: &mut String, value: usize, bits: usize, [zero, one]: [char; 2]) {
+    for bit in (0..bits).rev() {
+        code.push(if value >> bit & 1 == 1 { one } else { zero });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_the_puzzle_examples() {
+        let cabin = Cabin::default();
+        for (code, row, column, seat_id) in [
+            ("FBFBBFFRLR", 44, 5, 357),
+            ("BFFFBBFRRR", 70, 7, 567),
+            ("FFFBBBFRRR", 14, 7, 119),
+            ("BBFFBBFRLL", 102, 4, 820),
+        ] {
+            let pass = cabin.decode(code).unwrap();
+            assert_eq!(
+                (pass.row(), pass.column(), pass.seat_id()),
+                (row, column, seat_id)
+            );
+            assert_eq!(cabin.encode(seat_id).unwrap(), code);
+        }
+    }
+
+    #[test]
+    fn round_trips_every_seat_in_odd_shaped_cabins() {
+        for (rows, columns, length) in [(5, 3, 5), (1, 10, 4), (7, 1, 3), (1, 1, 0), (200, 9, 12)] {
+            let cabin = Cabin::new(rows, columns).unwrap();
+            for seat_id in 0..cabin.seats() {
+                let code = cabin.encode(seat_id).unwrap();
+                assert_eq!(code.len(), length, "{}", code);
+                let pass = cabin.decode(&code).unwrap();
+                assert_eq!(pass.seat_id(), seat_id);
+                assert_eq!(pass, cabin.seat(seat_id).unwrap());
+            }
+            assert!(cabin.encode(cabin.seats()).is_err());
+        }
+    }
+
+    #[test]
+    fn rejects_bad_characters() {
+        let cabin = Cabin::default();
+        assert_eq!(
+            cabin.decode("FBFBBFLRLR").unwrap_err(),
+            "\"FBFBBFLRLR\" has 'L' at position 7, where it should have F or B"
+        );
+        assert_eq!(
+            cabin.decode("FBFBBFFRFR").unwrap_err(),
+            "\"FBFBBFFRFR\" has 'F' at position 9, where it should have L or R"
+        );
+        assert_eq!(
+            cabin.decode("fbfbbffrlr").unwrap_err(),
+            "\"fbfbbffrlr\" has 'f' at position 1, where it should have F or B"
+        );
+    }
+
+    #[test]
+    fn rejects_the_wrong_length() {
+        let cabin = Cabin::default();
+        assert_eq!(
+            cabin.decode("FBFBBFFRL").unwrap_err(),
+            "\"FBFBBFFRL\" is 9 characters long, but passes for this cabin have 10"
+        );
+        assert!(cabin.decode("FBFBBFFRLRR").is_err());
+        assert!(cabin.decode("").is_err());
+        // counted in characters, not bytes
+        assert!(cabin.decode("FBFBBFFRLé").is_err());
+        assert!(Cabin::new(1, 1).unwrap().decode("").is_ok());
+    }
+
+    #[test]
+    fn rejects_seats_past_the_edge() {
+        let cabin = Cabin::new(5, 3).unwrap();
+        assert_eq!(
+            cabin.decode("BFBLL").unwrap_err(),
+            "\"BFBLL\" is for row 5, but the cabin has 5"
+        );
+        assert_eq!(
+            cabin.decode("FFFRR").unwrap_err(),
+            "\"FFFRR\" is for column 3, but the cabin has 3"
+        );
+        assert_eq!(
+            cabin.encode(15).unwrap_err(),
+            "Seat 15 is past the last seat in the cabin (14)"
+        );
+        assert!(Cabin::new(0, 8).is_err());
+        assert!(Cabin::new(8, 0).is_err());
+    }
+}
@@ -0,0 +1,210 @@
+use common::parse_groups;
+use common::solution::Solution;
+use std::str::FromStr;
+
+pub const QUESTIONS: usize = 26;
+
+pub struct Day6;
+
+impl Solution for Day6 {
+    type Input = Vec<CustomsGroup>;
+    type Part1 = u32;
+    type Part2 = u32;
+
+    fn parse(src: &str) -> Result<Vec<CustomsGroup>, String> {
+        parse_groups(src)
+    }
+
+    fn part1(groups: &Vec<CustomsGroup>) -> u32 {
+        groups.iter().map(|group| group.anyone().count_ones()).sum()
+    }
+
+    fn part2(groups: &Vec<CustomsGroup>) -> u32 {
+        groups
+            .iter()
+            .map(|group| group.everyone().count_ones())
+            .sum()
+    }
+}
+
+/// One group's answers, a line per person. Each person's answers are a bitmask with bit 0
+/// for question a up to bit 25 for question z, and the queries return masks the same way.
+#[derive(Debug)]
+pub struct CustomsGroup {
+    people: Vec<u32>,
+}
+
+impl CustomsGroup {
+    pub fn people(&self) -> &[u32] {
+        &self.people
+    }
+
+    pub fn anyone(&self) -> u32 {
+        self.people.iter().fold(0, |acc, person| acc | person)
+    }
+
+    pub fn everyone(&self) -> u32 {
+        self.people.iter().fold(!0, |acc, person| acc & person)
+    }
+
+    pub fn exactly_one(&self) -> u32 {
+        let (mut once, mut more) = (0, 0);
+        for &person in &self.people {
+            more |= once & person;
+            once = (once | person) & !more;
+        }
+        once
+    }
+
+    pub fn at_least(&self, k: usize) -> u32 {
+        self.counts()
+            .iter()
+            .enumerate()
+            .filter(|&(_, &count)| count >= k)
+            .fold(0, |mask, (question, _)| mask | 1 << question)
+    }
+
+    // how many people answered each question
+    pub fn counts(&self) -> [usize; QUESTIONS] {
+        let mut counts = [0; QUESTIONS];
+        for &person in &self.people {
+            for (question, count) in counts.iter_mut().enumerate() {
+                *count += (person >> question & 1) as usize;
+            }
+        }
+        counts
+    }
+}
+
+// how many people answered each question, across every group
+pub fn histogram(groups: &[CustomsGroup]) -> [usize; QUESTIONS] {
+    let mut totals = [0; QUESTIONS];
+    for group in groups {
+        for (total, count) in totals.iter_mut().zip(group.counts()) {
+            *total += count;
+        }
+    }
+    totals
+}
+
+pub fn question(idx: usize) -> char {
+    (b'a' + idx as u8) as char
+}
+
+impl FromStr for CustomsGroup {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let people = s
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                line.chars().try_fold(0, |answers, c| {
+                    if c.is_ascii_lowercase() {
+                        Ok(answers | 1 << (c as u8 - b'a'))
+                    } else {
+                        Err(format!(
+                            "{:?} in {:?} is not a question; questions are a to z",
+                            c, line
+                        ))
+                    }
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if people.is_empty() {
+            return Err("A group has nobody in it".into());
+        }
+        Ok(CustomsGroup { people })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn group(src: &str) -> CustomsGroup {
+        src.parse().unwrap()
+    }
+
+    // the questions in a mask, as letters
+    fn letters(mask: u32) -> String {
+        (0..QUESTIONS)
+            .filter(|&idx| mask >> idx & 1 == 1)
+            .map(question)
+            .collect()
+    }
+
+    #[test]
+    fn anyone_everyone_and_exactly_one() {
+        let g = group("abcx\nabcy\nabcz\nax\n");
+        assert_eq!(letters(g.anyone()), "abcxyz");
+        assert_eq!(letters(g.everyone()), "a");
+        assert_eq!(letters(g.exactly_one()), "yz");
+
+        // a question answered three times is not answered exactly once
+        let g = group("ab\na\na\nc");
+        assert_eq!(letters(g.exactly_one()), "bc");
+        let g = group("a");
+        assert_eq!(letters(g.exactly_one()), "a");
+        assert_eq!(letters(g.everyone()), "a");
+    }
+
+    #[test]
+    fn quorums() {
+        let g = group("abcx\nabcy\nabcz\nax\n");
+        assert_eq!(letters(g.at_least(0)), "abcdefghijklmnopqrstuvwxyz");
+        assert_eq!(letters(g.at_least(1)), letters(g.anyone()));
+        assert_eq!(letters(g.at_least(2)), "abcx");
+        assert_eq!(letters(g.at_least(3)), "abc");
+        assert_eq!(letters(g.at_least(4)), letters(g.everyone()));
+        assert_eq!(g.at_least(5), 0);
+        assert_eq!(g.counts()[0], 4);
+        assert_eq!(g.counts()[23], 2);
+    }
+
+    #[test]
+    fn all_twenty_six_questions() {
+        let alphabet = "abcdefghijklmnopqrstuvwxyz";
+        let reversed: String = alphabet.chars().rev().collect();
+        let g = group(&format!("{}\n{}", alphabet, reversed));
+        assert_eq!(g.people(), [(1 << 26) - 1; 2]);
+        assert_eq!(g.everyone().count_ones(), 26);
+        assert_eq!(g.everyone(), g.anyone());
+        assert_eq!(g.exactly_one(), 0);
+        assert_eq!(g.counts(), [2; QUESTIONS]);
+        assert_eq!(question(25), 'z');
+    }
+
+    #[test]
+    fn repeated_answers_count_once() {
+        let g = group("aaa\nab");
+        assert_eq!(g.counts()[0], 2);
+        assert_eq!(letters(g.exactly_one()), "b");
+    }
+
+    #[test]
+    fn rejects_empty_groups_and_other_characters() {
+        assert_eq!(
+            CustomsGroup::from_str("").unwrap_err(),
+            "A group has nobody in it"
+        );
+        assert_eq!(
+            CustomsGroup::from_str(" \n\n").unwrap_err(),
+            "A group has nobody in it"
+        );
+        assert_eq!(
+            CustomsGroup::from_str("ab\naB").unwrap_err(),
+            "'B' in \"aB\" is not a question; questions are a to z"
+        );
+    }
+
+    #[test]
+    fn histogram_sums_every_group() {
+        let groups = Day6::parse("abc\n\na\nb\nc\n\nab\nac\n\na\na\na\na\n\nb").unwrap();
+        let totals = histogram(&groups);
+        assert_eq!(&totals[..4], [8, 4, 3, 0]);
+        assert_eq!(Day6::part1(&groups), 11);
+        assert_eq!(Day6::part2(&groups), 6);
+    }
+}
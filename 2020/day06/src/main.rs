@@ -0,0 +1,52 @@
+use aoc2020_day06::{histogram, question, CustomsGroup, Day6};
+use common::cli::{self, Args, Cli};
+
+const USAGE: &str = "usage:
+  aoc2020-day06
+  aoc2020-day06 at-least <k> [--input FILE]
+  aoc2020-day06 exactly-one [--input FILE]
+  aoc2020-day06 histogram [--input FILE]
+
+`at-least` sums, over every group, the questions answered by k or more of its people;
+`exactly-one` those answered by just one. `histogram` counts how many people answered
+each question across all the groups.";
+
+const CLI: Cli = Cli {
+    usage: USAGE,
+    switches: &[],
+    options: &[],
+};
+
+fn main() {
+    cli::main::<Day6>(2020, 6, &CLI, run);
+}
+
+fn run(command: &str, args: &Args) -> Result<(), String> {
+    match (command, &args.positional[..]) {
+        ("at-least", [k]) => {
+            let k = args.parse_arg(k, "k")?;
+            print_total(&args.load::<Day6>(2020, 6)?, |group| group.at_least(k));
+            Ok(())
+        }
+        ("at-least", _) => Err(args.usage_error("Expected <k>".into())),
+        ("exactly-one", []) => {
+            print_total(&args.load::<Day6>(2020, 6)?, CustomsGroup::exactly_one);
+            Ok(())
+        }
+        ("histogram", []) => {
+            let totals = histogram(&args.load::<Day6>(2020, 6)?);
+            let most = totals.iter().copied().max().unwrap_or(0).max(1);
+            for (idx, total) in totals.iter().enumerate() {
+                let bar = "#".repeat(total * 50 / most);
+                println!("{} {:>6} {}", question(idx), total, bar);
+            }
+            Ok(())
+        }
+        _ => Err(args.unknown_command(command)),
+    }
+}
+
+fn print_total<F: Fn(&CustomsGroup) -> u32>(groups: &[CustomsGroup], query: F) {
+    let total: u32 = groups.iter().map(|group| query(group).count_ones()).sum();
+    println!("{}", total);
+}
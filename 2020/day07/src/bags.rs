@@ -0,0 +1,264 @@
+use crate::validate::validate;
+use crate::{Colour, Rule};
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::fmt::{self, Display};
+
+/// The rules indexed both ways: what each bag holds, and what holds each bag.
+pub struct BagRules {
+    contents: HashMap<Colour, Vec<(usize, Colour)>>,
+    containers: HashMap<Colour, Vec<Colour>>,
+}
+
+/// A bag and everything inside it, with how many of each bag its container holds.
+pub struct ContentsTree {
+    pub count: usize,
+    pub colour: Colour,
+    pub children: Vec<ContentsTree>,
+}
+
+impl BagRules {
+    // refuses rule sets with any of the problems `validate` looks for
+    pub fn new(rules: &[Rule]) -> Result<BagRules, String> {
+        let problems = validate(rules);
+        if !problems.is_empty() {
+            let problems: Vec<_> = problems.iter().map(|p| format!("  {}", p)).collect();
+            return Err(format!(
+                "The rules have {} problem(s):\n{}",
+                problems.len(),
+                problems.join("\n")
+            ));
+        }
+
+        let mut contents = HashMap::new();
+        let mut containers: HashMap<Colour, Vec<Colour>> = HashMap::new();
+        for rule in rules {
+            for (_, inner) in &rule.inner {
+                containers
+                    .entry(inner.clone())
+                    .or_default()
+                    .push(rule.outer.clone());
+            }
+            contents.insert(rule.outer.clone(), rule.inner.clone());
+        }
+        Ok(BagRules {
+            contents,
+            containers,
+        })
+    }
+
+    pub fn colours(&self) -> impl Iterator<Item = &str> {
+        self.contents.keys().map(|colour| &colour[..])
+    }
+
+    // every bag that holds `colour`, directly or inside other bags
+    pub fn containers_of(&self, colour: &str) -> Result<BTreeSet<&str>, String> {
+        self.check_defined(colour)?;
+        let mut seen = BTreeSet::new();
+        let mut unprocessed = vec![colour];
+        while let Some(colour) = unprocessed.pop() {
+            for container in self.containers.get(colour).into_iter().flatten() {
+                if seen.insert(&container[..]) {
+                    unprocessed.push(container);
+                }
+            }
+        }
+        Ok(seen)
+    }
+
+    // how many bags `colour` holds in all, not counting itself
+    pub fn total_contents(&self, colour: &str) -> Result<usize, String> {
+        self.check_defined(colour)?;
+        let mut totals = HashMap::new();
+        Ok(self.total_including(colour, &mut totals) - 1)
+    }
+
+    fn total_including<'a>(
+        &'a self,
+        colour: &'a str,
+        totals: &mut HashMap<&'a str, usize>,
+    ) -> usize {
+        if let Some(&total) = totals.get(colour) {
+            return total;
+        }
+        let total = 1 + self
+            .inner(colour)
+            .iter()
+            .map(|(count, inner)| count * self.total_including(inner, totals))
+            .sum::<usize>();
+        totals.insert(colour, total);
+        total
+    }
+
+    pub fn contents_tree(&self, colour: &str) -> Result<ContentsTree, String> {
+        self.check_defined(colour)?;
+        Ok(self.tree(1, colour))
+    }
+
+    fn tree(&self, count: usize, colour: &str) -> ContentsTree {
+        ContentsTree {
+            count,
+            colour: colour.into(),
+            children: self
+                .inner(colour)
+                .iter()
+                .map(|(count, inner)| self.tree(*count, inner))
+                .collect(),
+        }
+    }
+
+    // the shortest chain of bags from `outer` in to `inner`, both included
+    pub fn path_between(&self, outer: &str, inner: &str) -> Result<Option<Vec<&str>>, String> {
+        self.check_defined(outer)?;
+        self.check_defined(inner)?;
+        let outer = &self.contents.get_key_value(outer).unwrap().0[..];
+        let mut came_from: HashMap<&str, &str> = HashMap::new();
+        let mut queue = VecDeque::from([outer]);
+        while let Some(colour) = queue.pop_front() {
+            if colour == inner {
+                let mut path = vec![colour];
+                while let Some(&prev) = came_from.get(path.last().unwrap()) {
+                    path.push(prev);
+                }
+                path.reverse();
+                return Ok(Some(path));
+            }
+            for (_, next) in self.inner(colour) {
+                if next != outer && !came_from.contains_key(&next[..]) {
+                    came_from.insert(next, colour);
+                    queue.push_back(next);
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    // every colour has a rule once the rules have been validated
+    fn inner(&self, colour: &str) -> &[(usize, Colour)] {
+        &self.contents[colour]
+    }
+
+    fn check_defined(&self, colour: &str) -> Result<(), String> {
+        if self.contents.contains_key(colour) {
+            Ok(())
+        } else {
+            Err(format!("Unknown colour: {}", colour))
+        }
+    }
+}
+
+impl ContentsTree {
+    fn write(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+        writeln!(f, "{}{} {}", "  ".repeat(depth), self.count, self.colour)?;
+        self.children
+            .iter()
+            .try_for_each(|child| child.write(f, depth + 1))
+    }
+}
+
+impl Display for ContentsTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Day7;
+    use common::solution::Solution;
+
+    const EXAMPLE: &str = "\
+light red bags contain 1 bright white bag, 2 muted yellow bags.
+dark orange bags contain 3 bright white bags, 4 muted yellow bags.
+bright white bags contain 1 shiny gold bag.
+muted yellow bags contain 2 shiny gold bags, 9 faded blue bags.
+shiny gold bags contain 1 dark olive bag, 2 vibrant plum bags.
+dark olive bags contain 3 faded blue bags, 4 dotted black bags.
+vibrant plum bags contain 5 faded blue bags, 6 dotted black bags.
+faded blue bags contain no other bags.
+dotted black bags contain no other bags.";
+
+    fn rules(src: &str) -> BagRules {
+        Day7::parse(src).unwrap()
+    }
+
+    #[test]
+    fn queries_any_colour() {
+        let bags = rules(EXAMPLE);
+        assert_eq!(
+            bags.containers_of("shiny gold").unwrap(),
+            BTreeSet::from(["bright white", "muted yellow", "dark orange", "light red"])
+        );
+        assert_eq!(
+            bags.containers_of("faded blue").unwrap().len(),
+            7,
+            "everything but dotted black and faded blue itself"
+        );
+        assert!(bags.containers_of("light red").unwrap().is_empty());
+
+        assert_eq!(bags.total_contents("shiny gold").unwrap(), 32);
+        assert_eq!(bags.total_contents("dark olive").unwrap(), 7);
+        assert_eq!(bags.total_contents("faded blue").unwrap(), 0);
+        // 1 white (1 + 32) + 2 yellow (1 + 2 * 33 + 9)
+        assert_eq!(bags.total_contents("light red").unwrap(), 186);
+        assert_eq!(bags.colours().count(), 9);
+    }
+
+    #[test]
+    fn unknown_colours_are_errors() {
+        let bags = rules(EXAMPLE);
+        for result in [
+            bags.containers_of("plaid").map(|_| ()),
+            bags.total_contents("plaid").map(|_| ()),
+            bags.contents_tree("plaid").map(|_| ()),
+            bags.path_between("plaid", "faded blue").map(|_| ()),
+            bags.path_between("light red", "plaid").map(|_| ()),
+        ] {
+            assert_eq!(result.unwrap_err(), "Unknown colour: plaid");
+        }
+
+        // rules that mention a bag without defining it are refused up front
+        assert_eq!(
+            Day7::parse("red bags contain 2 blue bags.").err().unwrap(),
+            "The rules have 1 problem(s):\n  \
+             rule 1: red bags contain blue bags, but there's no rule for blue bags"
+        );
+    }
+
+    #[test]
+    fn shortest_paths() {
+        let bags = rules(EXAMPLE);
+        assert_eq!(
+            bags.path_between("light red", "faded blue").unwrap(),
+            Some(vec!["light red", "muted yellow", "faded blue"])
+        );
+        assert_eq!(
+            bags.path_between("dark orange", "dotted black").unwrap(),
+            Some(vec![
+                "dark orange",
+                "bright white",
+                "shiny gold",
+                "dark olive",
+                "dotted black"
+            ])
+        );
+        assert_eq!(
+            bags.path_between("faded blue", "faded blue").unwrap(),
+            Some(vec!["faded blue"])
+        );
+        assert_eq!(bags.path_between("shiny gold", "light red").unwrap(), None);
+    }
+
+    #[test]
+    fn renders_the_contents_tree() {
+        let bags = rules(EXAMPLE);
+        assert_eq!(
+            bags.contents_tree("dark olive").unwrap().to_string(),
+            "1 dark olive\n  3 faded blue\n  4 dotted black\n"
+        );
+        let tree = bags.contents_tree("bright white").unwrap();
+        assert_eq!(tree.children.len(), 1);
+        assert_eq!(tree.children[0].children[1].colour, "vibrant plum");
+        assert_eq!(tree.children[0].children[1].count, 2);
+    }
+}
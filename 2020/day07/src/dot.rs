@@ -0,0 +1,198 @@
+use crate::Rule;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use std::str::FromStr;
+
+/// Which way to follow containment out from a chosen bag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    // the bags it holds
+    Contents,
+    // the bags that hold it
+    Containers,
+    Both,
+}
+
+impl FromStr for Direction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "contents" => Ok(Direction::Contents),
+            "containers" => Ok(Direction::Containers),
+            "both" => Ok(Direction::Both),
+            _ => Err(format!("Unknown direction: {}", s)),
+        }
+    }
+}
+
+// The containment graph in Graphviz's DOT language, with an edge from each bag to each
+// bag it holds, labelled with how many. Given a focus, only the bags it reaches in that
+// direction are drawn, and the focus itself is highlighted.
+pub fn to_dot(rules: &[Rule], focus: Option<(&str, Direction)>) -> Result<String, String> {
+    let mut edges: Vec<(&str, usize, &str)> = vec![];
+    let mut colours: Vec<&str> = vec![];
+    let mut seen = HashSet::new();
+    for rule in rules {
+        for colour in std::iter::once(&rule.outer).chain(rule.inner.iter().map(|(_, c)| c)) {
+            if seen.insert(&colour[..]) {
+                colours.push(colour);
+            }
+        }
+        edges.extend(
+            rule.inner
+                .iter()
+                .map(|(count, inner)| (&rule.outer[..], *count, &inner[..])),
+        );
+    }
+
+    let shown = match focus {
+        None => seen,
+        Some((colour, direction)) => {
+            if !seen.contains(colour) {
+                return Err(format!("Unknown colour: {}", colour));
+            }
+            let mut shown = HashSet::from([colour]);
+            if direction != Direction::Containers {
+                shown.extend(reachable(colour, edges.iter().map(|&(o, _, i)| (o, i))));
+            }
+            if direction != Direction::Contents {
+                shown.extend(reachable(colour, edges.iter().map(|&(o, _, i)| (i, o))));
+            }
+            shown
+        }
+    };
+
+    let mut out = String::from("digraph bags {\n    rankdir=LR;\n    node [shape=box];\n");
+    for colour in colours.iter().filter(|colour| shown.contains(*colour)) {
+        let highlight = match focus {
+            Some((focus, _)) if focus == *colour => " [style=filled, fillcolor=gold]",
+            _ => "",
+        };
+        writeln!(out, "    {}{};", quote(colour), highlight).unwrap();
+    }
+    for (outer, count, inner) in edges {
+        if shown.contains(outer) && shown.contains(inner) {
+            writeln!(
+                out,
+                "    {} -> {} [label=\"{}\"];",
+                quote(outer),
+                quote(inner),
+                count
+            )
+            .unwrap();
+        }
+    }
+    out.push_str("}\n");
+    Ok(out)
+}
+
+fn reachable<'a, I>(start: &'a str, edges: I) -> HashSet<&'a str>
+where
+    I: Iterator<Item = (&'a str, &'a str)>,
+{
+    let mut next: HashMap<&str, Vec<&str>> = HashMap::new();
+    for (from, to) in edges {
+        next.entry(from).or_default().push(to);
+    }
+    let mut seen = HashSet::new();
+    let mut unprocessed = vec![start];
+    while let Some(colour) = unprocessed.pop() {
+        for &to in next.get(colour).into_iter().flatten() {
+            if seen.insert(to) {
+                unprocessed.push(to);
+            }
+        }
+    }
+    seen
+}
+
+fn quote(colour: &str) -> String {
+    format!("\"{}\"", colour.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_rules;
+
+    const RULES: &str = "\
+light red bags contain 1 bright white bag, 2 muted yellow bags.
+bright white bags contain 1 shiny gold bag.
+muted yellow bags contain 2 shiny gold bags.
+shiny gold bags contain 3 faded blue bags.
+faded blue bags contain no other bags.
+plain bags contain no other bags.";
+
+    fn dot(focus: Option<(&str, Direction)>) -> Result<String, String> {
+        to_dot(&parse_rules(RULES).unwrap(), focus)
+    }
+
+    #[test]
+    fn draws_every_bag_and_edge() {
+        assert_eq!(
+            dot(None).unwrap(),
+            r#"digraph bags {
+    rankdir=LR;
+    node [shape=box];
+    "light red";
+    "bright white";
+    "muted yellow";
+    "shiny gold";
+    "faded blue";
+    "plain";
+    "light red" -> "bright white" [label="1"];
+    "light red" -> "muted yellow" [label="2"];
+    "bright white" -> "shiny gold" [label="1"];
+    "muted yellow" -> "shiny gold" [label="2"];
+    "shiny gold" -> "faded blue" [label="3"];
+}
+"#
+        );
+    }
+
+    #[test]
+    fn draws_only_what_the_focus_reaches() {
+        assert_eq!(
+            dot(Some(("muted yellow", Direction::Contents))).unwrap(),
+            r#"digraph bags {
+    rankdir=LR;
+    node [shape=box];
+    "muted yellow" [style=filled, fillcolor=gold];
+    "shiny gold";
+    "faded blue";
+    "muted yellow" -> "shiny gold" [label="2"];
+    "shiny gold" -> "faded blue" [label="3"];
+}
+"#
+        );
+        let containers = dot(Some(("shiny gold", Direction::Containers))).unwrap();
+        assert!(containers.contains("\"light red\" -> \"muted yellow\""));
+        assert!(!containers.contains("faded blue"));
+        let both = dot(Some(("bright white", Direction::Both))).unwrap();
+        assert!(both.contains("\"light red\" -> \"bright white\""));
+        assert!(both.contains("\"shiny gold\" -> \"faded blue\""));
+        // muted yellow is neither inside bright white nor around it
+        assert!(!both.contains("muted yellow"));
+        assert!(!both.contains("plain"));
+    }
+
+    #[test]
+    fn unknown_focus_is_an_error() {
+        assert_eq!(
+            dot(Some(("plaid", Direction::Both))).unwrap_err(),
+            "Unknown colour: plaid"
+        );
+        assert_eq!(
+            Direction::from_str("sideways").unwrap_err(),
+            "Unknown direction: sideways"
+        );
+    }
+
+    #[test]
+    fn quotes_names() {
+        assert_eq!(quote("light red"), r#""light red""#);
+        assert_eq!(quote(r#"say "hi""#), r#""say \"hi\"""#);
+        assert_eq!(quote(r"back\slash"), r#""back\\slash""#);
+    }
+}
@@ -0,0 +1,85 @@
+pub mod bags;
+pub mod dot;
+pub mod validate;
+
+use bags::BagRules;
+use common::parse_vec;
+use common::solution::{Answer, Solution};
+use lazy_static::*;
+use regex::Regex;
+use std::str::FromStr;
+
+type Colour = String;
+
+pub const MY_BAG: &str = "shiny gold";
+
+pub struct Day7;
+
+impl Solution for Day7 {
+    type Input = BagRules;
+    type Part1 = Answer<usize>;
+    type Part2 = Answer<usize>;
+
+    // refuses rules `validate` finds problems with
+    fn parse(src: &str) -> Result<BagRules, String> {
+        BagRules::new(&parse_rules(src)?)
+    }
+
+    // no answer if the rules never mention a shiny gold bag
+    fn part1(rules: &BagRules) -> Answer<usize> {
+        Answer(
+            rules
+                .containers_of(MY_BAG)
+                .ok()
+                .map(|containers| containers.len()),
+        )
+    }
+
+    fn part2(rules: &BagRules) -> Answer<usize> {
+        Answer(rules.total_contents(MY_BAG).ok())
+    }
+}
+
+// the rules as written, before they're checked
+pub fn parse_rules(src: &str) -> Result<Vec<Rule>, String> {
+    parse_vec(src)
+}
+
+#[derive(Debug)]
+pub struct Rule {
+    outer: Colour,
+    inner: Vec<(usize, Colour)>,
+}
+
+impl FromStr for Rule {
+    type Err = String;
+
+    fn from_str(src: &str) -> Result<Rule, Self::Err> {
+        lazy_static! {
+            static ref CONTAINED_BAG_RE: Regex =
+                Regex::new(r"^(\d+) ([a-zA-Z\s]+) bags?$").unwrap();
+        };
+
+        let (colour, rest) = src.split_once(" bags contain ").ok_or(format!(
+            "Expected <colour> bags contain <contents>. but found {}",
+            src
+        ))?;
+        let rest = rest.trim_end_matches('.');
+        let mut inner_bags = vec![];
+        for inner_bag in rest.split(", ") {
+            if inner_bag == "no other bags" {
+                continue;
+            }
+            let caps = CONTAINED_BAG_RE
+                .captures(inner_bag)
+                .ok_or(format!("{} did not match expected format", inner_bag))?;
+            let count = usize::from_str(&caps[1]).map_err(|e| format!("{} ({})", e, inner_bag))?;
+            inner_bags.push((count, caps[2].into()))
+        }
+
+        Ok(Rule {
+            outer: colour.into(),
+            inner: inner_bags,
+        })
+    }
+}
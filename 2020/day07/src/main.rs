@@ -0,0 +1,106 @@
+use aoc2020_day07::dot::{to_dot, Direction};
+use aoc2020_day07::validate::validate;
+use aoc2020_day07::{parse_rules, Day7, Rule};
+use common::cli::{self, Args, Cli};
+use common::solution::load_raw_input;
+
+const USAGE: &str = "usage:
+  aoc2020-day07
+  aoc2020-day07 containers <colour> [--input FILE]
+  aoc2020-day07 contents <colour> [--tree] [--input FILE]
+  aoc2020-day07 path <outer> <inner> [--input FILE]
+  aoc2020-day07 validate [--input FILE]
+  aoc2020-day07 dot [colour] [--direction contents|containers|both] [--input FILE]
+
+Colours are given whole, quoted: \"shiny gold\". `containers` lists every bag that ends up
+holding the colour, `contents` counts the bags inside it (or, with --tree, shows them
+nested), and `path` finds the shortest chain of bags from one to the other. They all
+check the rules first, which `validate` does on its own: it reports bags that end up
+inside themselves, colours with more than one rule, bags with no rule, and zero counts.
+`dot` writes the containment graph for Graphviz, with how many of each bag on the edges;
+given a colour, it draws only the bags it holds and those holding it (or one direction)
+and highlights it.";
+
+const CLI: Cli = Cli {
+    usage: USAGE,
+    switches: &["--tree"],
+    options: &[("--direction", "a direction")],
+};
+
+fn main() {
+    cli::main::<Day7>(2020, 7, &CLI, run);
+}
+
+fn run(command: &str, args: &Args) -> Result<(), String> {
+    let direction: Option<Direction> = args
+        .value("--direction")
+        .map(str::parse)
+        .transpose()
+        .map_err(|e| args.usage_error(e))?;
+    match (command, &args.positional[..]) {
+        ("containers", [colour]) => {
+            let rules = args.load::<Day7>(2020, 7)?;
+            let containers = rules.containers_of(colour)?;
+            for container in &containers {
+                println!("{}", container);
+            }
+            println!("{} bags can hold {}", containers.len(), colour);
+            Ok(())
+        }
+        ("contents", [colour]) => {
+            let rules = args.load::<Day7>(2020, 7)?;
+            if args.switch("--tree") {
+                print!("{}", rules.contents_tree(colour)?);
+            } else {
+                println!("{}", rules.total_contents(colour)?);
+            }
+            Ok(())
+        }
+        ("path", [outer, inner]) => {
+            let rules = args.load::<Day7>(2020, 7)?;
+            let path = rules
+                .path_between(outer, inner)?
+                .ok_or(format!("{} bags never hold {} bags", outer, inner))?;
+            println!("{}", path.join(" > "));
+            Ok(())
+        }
+        ("validate", []) => {
+            let rules = rules(args)?;
+            let problems = validate(&rules);
+            for problem in &problems {
+                println!("{}", problem);
+            }
+            if problems.is_empty() {
+                println!("{} rules, no problems", rules.len());
+                Ok(())
+            } else {
+                Err(format!("{} problem(s) found", problems.len()))
+            }
+        }
+        ("dot", []) if direction.is_none() => {
+            print!("{}", to_dot(&rules(args)?, None)?);
+            Ok(())
+        }
+        ("dot", [colour]) => {
+            let direction = direction.unwrap_or(Direction::Both);
+            print!("{}", to_dot(&rules(args)?, Some((colour, direction)))?);
+            Ok(())
+        }
+        ("dot", []) => Err(args.usage_error("--direction needs a colour".into())),
+        ("containers" | "contents", _) => Err(args.usage_error("Expected one colour".into())),
+        ("path", _) => Err(args.usage_error("Expected <outer> <inner>".into())),
+        _ => Err(args.unknown_command(command)),
+    }
+}
+
+// unchecked, for the commands that look at problems rather than refusing them
+fn rules(args: &Args) -> Result<Vec<Rule>, String> {
+    match args.input() {
+        Some(file_name) => {
+            let src =
+                std::fs::read_to_string(file_name).map_err(|e| format!("{}: {}", file_name, e))?;
+            parse_rules(&src).map_err(|e| format!("{}: {}", file_name, e))
+        }
+        None => parse_rules(&load_raw_input(2020, 7)?),
+    }
+}
@@ -0,0 +1,231 @@
+use crate::{Colour, Rule};
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Display};
+
+/// Something wrong with a rule set that would make queries on it meaningless. Rules are
+/// numbered from 1 in the order they're given.
+#[derive(Debug, PartialEq)]
+pub enum Problem {
+    // the colours in containment order, starting and ending with the same one
+    Cycle(Vec<Colour>),
+    Duplicate {
+        colour: Colour,
+        rules: Vec<usize>,
+    },
+    Dangling {
+        rule: usize,
+        outer: Colour,
+        inner: Colour,
+    },
+    ZeroCount {
+        rule: usize,
+        outer: Colour,
+        inner: Colour,
+    },
+}
+
+impl Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Problem::Cycle(colours) => {
+                write!(f, "bags end up inside themselves: {}", colours.join(" > "))
+            }
+            Problem::Duplicate { colour, rules } => {
+                let rules: Vec<_> = rules.iter().map(|rule| rule.to_string()).collect();
+                write!(
+                    f,
+                    "{} bags are defined by rules {}",
+                    colour,
+                    rules.join(", ")
+                )
+            }
+            Problem::Dangling { rule, outer, inner } => write!(
+                f,
+                "rule {}: {} bags contain {} bags, but there's no rule for {} bags",
+                rule, outer, inner, inner
+            ),
+            Problem::ZeroCount { rule, outer, inner } => {
+                write!(f, "rule {}: {} bags contain 0 {} bags", rule, outer, inner)
+            }
+        }
+    }
+}
+
+pub fn validate(rules: &[Rule]) -> Vec<Problem> {
+    let mut problems = vec![];
+
+    let mut definitions: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (idx, rule) in rules.iter().enumerate() {
+        definitions.entry(&rule.outer).or_default().push(idx + 1);
+    }
+    let mut reported = HashSet::new();
+    for rule in rules {
+        let numbers = &definitions[&rule.outer[..]];
+        if numbers.len() > 1 && reported.insert(&rule.outer[..]) {
+            problems.push(Problem::Duplicate {
+                colour: rule.outer.clone(),
+                rules: numbers.clone(),
+            });
+        }
+    }
+
+    for (idx, rule) in rules.iter().enumerate() {
+        for (count, inner) in &rule.inner {
+            let (rule, outer, inner) = (idx + 1, rule.outer.clone(), inner.clone());
+            if !definitions.contains_key(&inner[..]) {
+                problems.push(Problem::Dangling { rule, outer, inner });
+            } else if *count == 0 {
+                problems.push(Problem::ZeroCount { rule, outer, inner });
+            }
+        }
+    }
+
+    problems.extend(find_cycles(rules).into_iter().map(Problem::Cycle));
+    problems
+}
+
+// One cycle for each edge that closes one in a depth-first search, which finds at least
+// one cycle whenever there are any. The search keeps its own stack, so deep rule sets
+// can't overflow the real one.
+fn find_cycles(rules: &[Rule]) -> Vec<Vec<Colour>> {
+    let mut edges: HashMap<&str, Vec<&str>> = HashMap::new();
+    for rule in rules {
+        edges
+            .entry(&rule.outer)
+            .or_default()
+            .extend(rule.inner.iter().map(|(_, inner)| &inner[..]));
+    }
+
+    let mut cycles = vec![];
+    let mut done: HashSet<&str> = HashSet::new();
+    for rule in rules {
+        if done.contains(&rule.outer[..]) {
+            continue;
+        }
+        // the bags on the current path, each with how many of its edges have been followed,
+        // and where each of them is on it
+        let mut path: Vec<(&str, usize)> = vec![(&rule.outer, 0)];
+        let mut on_path: HashMap<&str, usize> = HashMap::from([(&rule.outer[..], 0)]);
+        while let Some(top) = path.len().checked_sub(1) {
+            let (colour, next) = path[top];
+            path[top].1 += 1;
+            let inner = edges.get(colour).and_then(|inner| inner.get(next)).copied();
+            match inner {
+                None => {
+                    done.insert(colour);
+                    on_path.remove(colour);
+                    path.pop();
+                }
+                Some(inner) if done.contains(inner) => {}
+                Some(inner) => match on_path.get(inner) {
+                    Some(&start) => {
+                        let mut cycle: Vec<Colour> =
+                            path[start..].iter().map(|(c, _)| c.to_string()).collect();
+                        cycle.push(inner.into());
+                        cycles.push(cycle);
+                    }
+                    None => {
+                        on_path.insert(inner, path.len());
+                        path.push((inner, 0));
+                    }
+                },
+            }
+        }
+    }
+    cycles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_rules;
+
+    fn problems(src: &str) -> Vec<String> {
+        validate(&parse_rules(src).unwrap())
+            .iter()
+            .map(|problem| problem.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn well_formed_rules_have_no_problems() {
+        let src = "\
+red bags contain 1 white bag, 2 yellow bags.
+white bags contain 3 yellow bags.
+yellow bags contain no other bags.";
+        assert!(problems(src).is_empty());
+    }
+
+    #[test]
+    fn finds_cycles() {
+        let src = "\
+red bags contain 1 white bag.
+white bags contain 2 blue bags.
+blue bags contain 1 red bag, 1 green bag.
+green bags contain 1 green bag.";
+        assert_eq!(
+            problems(src),
+            [
+                "bags end up inside themselves: red > white > blue > red",
+                "bags end up inside themselves: green > green",
+            ]
+        );
+        assert_eq!(
+            validate(&parse_rules("a bags contain 1 b bag.\nb bags contain 1 a bag.").unwrap()),
+            [Problem::Cycle(vec!["a".into(), "b".into(), "a".into()])]
+        );
+    }
+
+    #[test]
+    fn deep_chains_without_cycles_are_fine() {
+        // colours are letters only, so number them in base 26
+        let colour = |mut idx: usize| {
+            let mut name = String::from("c");
+            loop {
+                name.push((b'a' + (idx % 26) as u8) as char);
+                idx /= 26;
+                if idx == 0 {
+                    return name;
+                }
+            }
+        };
+        let depth = 100_000;
+        let src: Vec<_> = (0..depth)
+            .map(|idx| format!("{} bags contain 1 {} bag.", colour(idx), colour(idx + 1)))
+            .chain([format!("{} bags contain no other bags.", colour(depth))])
+            .collect();
+        assert!(problems(&src.join("\n")).is_empty());
+    }
+
+    #[test]
+    fn finds_duplicates_once_each() {
+        let src = "\
+red bags contain no other bags.
+blue bags contain no other bags.
+red bags contain 1 blue bag.
+red bags contain 2 blue bags.
+blue bags contain no other bags.";
+        assert_eq!(
+            problems(src),
+            [
+                "red bags are defined by rules 1, 3, 4",
+                "blue bags are defined by rules 2, 5",
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_dangling_bags_and_zero_counts() {
+        let src = "\
+red bags contain 1 white bag, 0 blue bags.
+blue bags contain 2 plaid bags.";
+        assert_eq!(
+            problems(src),
+            [
+                "rule 1: red bags contain white bags, but there's no rule for white bags",
+                "rule 1: red bags contain 0 blue bags",
+                "rule 2: blue bags contain plaid bags, but there's no rule for plaid bags",
+            ]
+        );
+    }
+}
@@ -0,0 +1,150 @@
+use crate::vm::{Flow, Instruction, InstructionSet, Operand, Program, Word};
+use std::collections::VecDeque;
+
+/// The edges between a program's instructions, worked out from each opcode's `Flow`
+/// without running anything. Node `len` stands for finishing cleanly.
+///
+/// A branch has an edge each way, so reaching the end means it can be reached for some
+/// values of the registers. A jump by a register could go anywhere, so it gets no edges
+/// but is taken to be able to reach the end. A jump before the start or more than one past
+/// the end gets no edges.
+pub struct ControlFlow {
+    successors: Vec<Vec<usize>>,
+    reaches_end: Vec<bool>,
+}
+
+impl ControlFlow {
+    pub fn new(program: &Program) -> ControlFlow {
+        let len = program.len();
+        let successors: Vec<_> = program
+            .instructions()
+            .iter()
+            .enumerate()
+            .map(|(idx, instruction)| successors(program.set(), len, idx, instruction))
+            .collect();
+
+        let mut predecessors = vec![vec![]; len + 1];
+        for (idx, targets) in successors.iter().enumerate() {
+            for &target in targets {
+                predecessors[target].push(idx);
+            }
+        }
+        // walk back from the end, and from anywhere that might jump to it
+        let mut reaches_end = vec![false; len + 1];
+        reaches_end[len] = true;
+        let mut queue = VecDeque::from([len]);
+        for (idx, instruction) in program.instructions().iter().enumerate() {
+            if jumps_by_register(program.set(), instruction) {
+                reaches_end[idx] = true;
+                queue.push_back(idx);
+            }
+        }
+        while let Some(idx) = queue.pop_front() {
+            for &prev in &predecessors[idx] {
+                if !std::mem::replace(&mut reaches_end[prev], true) {
+                    queue.push_back(prev);
+                }
+            }
+        }
+
+        ControlFlow {
+            successors,
+            reaches_end,
+        }
+    }
+
+    pub fn successors(&self, idx: usize) -> &[usize] {
+        &self.successors[idx]
+    }
+
+    // true for the end itself
+    pub fn reaches_end(&self, idx: usize) -> bool {
+        self.reaches_end[idx]
+    }
+}
+
+// where control can go after the instruction at idx in a program of len instructions,
+// whether or not that's what the program holds there
+pub fn successors(
+    set: &InstructionSet,
+    len: usize,
+    idx: usize,
+    instruction: &Instruction,
+) -> Vec<usize> {
+    let jump = |operand: usize| match instruction.operands[operand] {
+        Operand::Immediate(offset) => {
+            let target = (idx as Word).checked_add(offset)?;
+            Some(target as usize).filter(|_| 0 <= target && target as usize <= len)
+        }
+        Operand::Register(_) => None,
+    };
+    match set.def(instruction.opcode).flow {
+        Flow::Next => vec![idx + 1],
+        Flow::Jump(operand) => jump(operand).into_iter().collect(),
+        Flow::Branch(operand) => {
+            let mut targets = vec![idx + 1];
+            targets.extend(jump(operand).filter(|&target| target != idx + 1));
+            targets
+        }
+    }
+}
+
+// whether the program always takes the same path, whatever its registers hold: no
+// branches, and no jumps by a register
+pub fn is_static(program: &Program) -> bool {
+    program.instructions().iter().all(|instruction| {
+        let flow = program.set().def(instruction.opcode).flow;
+        !matches!(flow, Flow::Branch(_)) && !jumps_by_register(program.set(), instruction)
+    })
+}
+
+pub fn jumps_by_register(set: &InstructionSet, instruction: &Instruction) -> bool {
+    match set.def(instruction.opcode).flow {
+        Flow::Next => false,
+        Flow::Jump(operand) | Flow::Branch(operand) => {
+            matches!(instruction.operands[operand], Operand::Register(_))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+
+    fn program(src: &str) -> Program {
+        Program::parse(src, Rc::new(InstructionSet::default())).unwrap()
+    }
+
+    #[test]
+    fn edges_follow_each_opcodes_flow() {
+        let program = program("acc +1\njnz a +2\njmp -2\njmp +1\njz a -10");
+        let flow = ControlFlow::new(&program);
+        let edges: Vec<_> = (0..program.len()).map(|idx| flow.successors(idx)).collect();
+        assert_eq!(edges, [&[1][..], &[2, 3], &[0], &[4], &[5]]);
+        assert!((0..=program.len()).all(|idx| flow.reaches_end(idx)));
+    }
+
+    #[test]
+    fn jumps_that_overflow_have_no_edges() {
+        let program = program("nop +0\njmp +9223372036854775807\njz a +9223372036854775807");
+        let flow = ControlFlow::new(&program);
+        assert_eq!(flow.successors(1), &[] as &[usize]);
+        assert_eq!(flow.successors(2), [3]);
+        assert!(!flow.reaches_end(0));
+        assert!(flow.reaches_end(2));
+    }
+
+    #[test]
+    fn jumps_by_a_register_might_reach_the_end() {
+        let by_register = program("jmp +0\njmp a\njmp -1");
+        let flow = ControlFlow::new(&by_register);
+        assert_eq!(flow.successors(1), &[] as &[usize]);
+        assert!(!flow.reaches_end(0));
+        assert!(flow.reaches_end(1));
+        assert!(flow.reaches_end(2));
+        assert!(!is_static(&by_register));
+        assert!(is_static(&program("acc +1\njmp -1\nnop a")));
+        assert!(!is_static(&program("jnz a +1")));
+    }
+}
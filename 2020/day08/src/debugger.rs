@@ -0,0 +1,411 @@
+use crate::vm::{ExitReason, Machine, Opcode, Operand, Program, Register, Word};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+
+// how far `continue` goes looking for a breakpoint before giving up, since console
+// programs usually loop forever
+const CONTINUE_LIMIT: usize = 1_000_000;
+const CONTEXT: usize = 3;
+
+pub const HELP: &str = "commands:
+  step [n], s        run one instruction (or n)
+  back [n], b        undo one instruction (or n)
+  continue, c        run to the next breakpoint or watchpoint
+  until-loop, u      run until an instruction is about to run a second time
+  break <idx|op>     stop before the instruction at idx, or before any op instruction
+  delete <idx|op>    remove a breakpoint
+  watch <reg> [n]    stop when the register changes (or becomes n)
+  unwatch <reg>      remove a watchpoint
+  list [n]           show the instructions around the pointer, n either side
+  info               show the registers, breakpoints and watchpoints
+  reset              start again from the top, keeping breakpoints and watchpoints
+  quit, q            leave the debugger";
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Breakpoint {
+    At(usize),
+    Opcode(Opcode),
+}
+
+/// Runs a program an instruction at a time, remembering every state it passes through
+/// so it can step backwards as well as forwards.
+pub struct Debugger<'a> {
+    program: &'a Program,
+    machine: Machine,
+    // the machine before each instruction run so far
+    history: Vec<Machine>,
+    // how many times each instruction has run
+    visits: Vec<usize>,
+    breakpoints: BTreeSet<Breakpoint>,
+    // None to stop on any change
+    watchpoints: BTreeMap<Register, Option<Word>>,
+    quit: bool,
+}
+
+impl<'a> Debugger<'a> {
+    pub fn new(program: &'a Program) -> Self {
+        Debugger {
+            program,
+            machine: Machine::new(program.set()),
+            history: vec![],
+            visits: vec![0; program.len()],
+            breakpoints: BTreeSet::new(),
+            watchpoints: BTreeMap::new(),
+            quit: false,
+        }
+    }
+
+    // Reads commands a line at a time until `quit` or the end of the input. Scripts
+    // are echoed, so their output reads like a session.
+    pub fn repl<R: BufRead, W: Write>(
+        &mut self,
+        input: R,
+        mut out: W,
+        script: bool,
+    ) -> io::Result<()> {
+        write!(out, "{}", self.listing(CONTEXT))?;
+        let mut lines = input.lines();
+        while !self.quit {
+            if !script {
+                write!(out, "(dbg) ")?;
+                out.flush()?;
+            }
+            let line = match lines.next() {
+                Some(line) => line?,
+                None => break,
+            };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if script {
+                writeln!(out, "(dbg) {}", line)?;
+            }
+            match self.execute(line) {
+                Ok(output) => write!(out, "{}", output)?,
+                Err(e) => writeln!(out, "error: {}", e)?,
+            }
+        }
+        Ok(())
+    }
+
+    pub fn execute(&mut self, command: &str) -> Result<String, String> {
+        let words: Vec<_> = command.split_whitespace().collect();
+        let count = |arg: Option<&&str>| match arg {
+            Some(n) => usize::from_str(n).map_err(|_| format!("Invalid count: {}", n)),
+            None => Ok(1),
+        };
+        let (name, args) = match words.split_first() {
+            Some((name, args)) => (*name, args),
+            None => return Err("Expected a command; try help".into()),
+        };
+        match (name, args) {
+            ("step" | "s", args) if args.len() <= 1 => {
+                let stop = self.step(count(args.first())?);
+                Ok(self.stopped(stop))
+            }
+            ("back" | "b", args) if args.len() <= 1 => {
+                let n = count(args.first())?;
+                let undone = (0..n).take_while(|_| self.back()).count();
+                let stop = if undone < n {
+                    Some("at the start of the program".into())
+                } else {
+                    None
+                };
+                Ok(self.stopped(stop))
+            }
+            ("continue" | "c", []) => {
+                let stop = self.resume(false);
+                Ok(self.stopped(Some(stop)))
+            }
+            ("until-loop" | "u", []) => {
+                let stop = self.resume(true);
+                Ok(self.stopped(Some(stop)))
+            }
+            ("break", [target]) => {
+                self.breakpoints.insert(self.breakpoint(target)?);
+                Ok(String::new())
+            }
+            ("delete", [target]) => {
+                if self.breakpoints.remove(&self.breakpoint(target)?) {
+                    Ok(String::new())
+                } else {
+                    Err(format!("No breakpoint on {}", target))
+                }
+            }
+            ("watch", [register, rest @ ..]) if rest.len() <= 1 => {
+                let value = match rest.first() {
+                    Some(value) => Some(
+                        Word::from_str(value.trim_start_matches('+'))
+                            .map_err(|_| format!("Invalid value: {}", value))?,
+                    ),
+                    None => None,
+                };
+                self.watchpoints.insert(self.register(register)?, value);
+                Ok(String::new())
+            }
+            ("unwatch", [register]) => {
+                let register = self.register(register)?;
+                match self.watchpoints.remove(&register) {
+                    Some(_) => Ok(String::new()),
+                    None => Err(format!("No watchpoint on {}", args[0])),
+                }
+            }
+            ("list", args) if args.len() <= 1 => {
+                let context = match args.first() {
+                    Some(n) => count(Some(n))?,
+                    None => CONTEXT,
+                };
+                Ok(self.listing(context))
+            }
+            ("info", []) => Ok(self.info()),
+            ("reset", []) => {
+                self.machine = Machine::new(self.program.set());
+                self.history.clear();
+                self.visits.iter_mut().for_each(|visits| *visits = 0);
+                Ok(self.stopped(None))
+            }
+            ("quit" | "q", []) => {
+                self.quit = true;
+                Ok(String::new())
+            }
+            ("help", []) => Ok(format!("{}\n", HELP)),
+            _ => Err(format!("Unknown command: {}; try help", command)),
+        }
+    }
+
+    // runs up to n instructions, stopping early if the program does
+    fn step(&mut self, n: usize) -> Option<String> {
+        for _ in 0..n {
+            if let Err(reason) = self.step_once() {
+                return Some(reason.to_string());
+            }
+        }
+        None
+    }
+
+    fn step_once(&mut self) -> Result<(), ExitReason> {
+        let at = self.machine.instruction_ptr;
+        if at == self.program.len() {
+            return Err(ExitReason::EndOfScript);
+        } else if at > self.program.len() {
+            let from = self.history.last().map_or(0, |last| last.instruction_ptr);
+            return Err(ExitReason::InvalidJump {
+                from,
+                target: Some(at as Word),
+            });
+        }
+        let before = self.machine.clone();
+        if let Err(reason) = self.machine.step(self.program) {
+            self.machine = before;
+            return Err(reason);
+        }
+        self.visits[at] += 1;
+        self.history.push(before);
+        Ok(())
+    }
+
+    fn back(&mut self) -> bool {
+        match self.history.pop() {
+            Some(before) => {
+                self.visits[before.instruction_ptr] -= 1;
+                self.machine = before;
+                true
+            }
+            None => false,
+        }
+    }
+
+    // always runs at least one instruction, so continuing from a breakpoint moves on
+    fn resume(&mut self, until_loop: bool) -> String {
+        for _ in 0..CONTINUE_LIMIT {
+            let before = self.machine.clone();
+            if let Err(reason) = self.step_once() {
+                return reason.to_string();
+            }
+            for (&register, &target) in &self.watchpoints {
+                let (old, new) = (
+                    before.read(Operand::Register(register)),
+                    self.machine.read(Operand::Register(register)),
+                );
+                if old != new && target.is_none_or(|target| new == target) {
+                    let name = &self.program.set().registers()[register.0];
+                    return format!("watchpoint: {} changed from {} to {}", name, old, new);
+                }
+            }
+            let at = self.machine.instruction_ptr;
+            if at < self.program.len() {
+                let opcode = self.program.instructions()[at].opcode;
+                if self.breakpoints.contains(&Breakpoint::At(at))
+                    || self.breakpoints.contains(&Breakpoint::Opcode(opcode))
+                {
+                    return format!("breakpoint at {}", at);
+                }
+                if until_loop && self.visits[at] > 0 {
+                    return format!("instruction {} is about to run a second time", at);
+                }
+            }
+        }
+        format!("no breakpoint within {} instructions", CONTINUE_LIMIT)
+    }
+
+    fn stopped(&self, reason: Option<String>) -> String {
+        let mut out = String::new();
+        if let Some(reason) = reason {
+            writeln!(out, "stopped: {}", reason).unwrap();
+        }
+        writeln!(out, "step {}, {}", self.history.len(), self.registers()).unwrap();
+        out.push_str(&self.listing(CONTEXT));
+        out
+    }
+
+    // the instructions around the pointer; => marks the pointer and * a breakpoint
+    fn listing(&self, context: usize) -> String {
+        let at = self.machine.instruction_ptr;
+        let first = at.saturating_sub(context);
+        let last = (at + context).min(self.program.len().saturating_sub(1));
+        let mut out = String::new();
+        for idx in first..=last.max(first) {
+            if idx >= self.program.len() {
+                break;
+            }
+            let opcode = self.program.instructions()[idx].opcode;
+            let breakpoint = self.breakpoints.contains(&Breakpoint::At(idx))
+                || self.breakpoints.contains(&Breakpoint::Opcode(opcode));
+            writeln!(
+                out,
+                "{}{}{:>5}  {}",
+                if idx == at { "=>" } else { "  " },
+                if breakpoint { "*" } else { " " },
+                idx,
+                self.program.format(idx)
+            )
+            .unwrap();
+        }
+        if at >= self.program.len() {
+            writeln!(out, "=> {:>5}  (past the end)", at).unwrap();
+        }
+        out
+    }
+
+    fn info(&self) -> String {
+        let mut out = format!(
+            "ip {}, step {}, {}\n",
+            self.machine.instruction_ptr,
+            self.history.len(),
+            self.registers()
+        );
+        for breakpoint in &self.breakpoints {
+            match breakpoint {
+                Breakpoint::At(idx) => writeln!(out, "break {}", idx).unwrap(),
+                Breakpoint::Opcode(opcode) => {
+                    writeln!(out, "break {}", self.program.set().def(*opcode).name).unwrap()
+                }
+            }
+        }
+        for (register, target) in &self.watchpoints {
+            let name = &self.program.set().registers()[register.0];
+            match target {
+                Some(value) => writeln!(out, "watch {} {}", name, value).unwrap(),
+                None => writeln!(out, "watch {}", name).unwrap(),
+            }
+        }
+        out
+    }
+
+    fn registers(&self) -> String {
+        let registers: Vec<_> = self
+            .program
+            .set()
+            .registers()
+            .iter()
+            .zip(self.machine.registers())
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect();
+        registers.join(" ")
+    }
+
+    fn breakpoint(&self, target: &str) -> Result<Breakpoint, String> {
+        if let Ok(idx) = usize::from_str(target) {
+            if idx >= self.program.len() {
+                return Err(format!("The program has no instruction {}", idx));
+            }
+            return Ok(Breakpoint::At(idx));
+        }
+        self.program
+            .set()
+            .opcode(target)
+            .map(Breakpoint::Opcode)
+            .ok_or(format!(
+                "{} is neither an instruction index nor an opcode",
+                target
+            ))
+    }
+
+    fn register(&self, name: &str) -> Result<Register, String> {
+        self.program
+            .set()
+            .register_named(name)
+            .ok_or(format!("Unknown register: {}", name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::InstructionSet;
+    use std::rc::Rc;
+
+    const EXAMPLE: &str = "nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6";
+
+    fn session(script: &str) -> String {
+        let program = Program::parse(EXAMPLE, Rc::new(InstructionSet::default())).unwrap();
+        let mut out = vec![];
+        Debugger::new(&program)
+            .repl(script.as_bytes(), &mut out, true)
+            .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn scripted_session() {
+        let out = session("break 6\ncontinue\ninfo\nstep 2\nback\ninfo\nquit\nstep");
+        let stops: Vec<_> = out
+            .lines()
+            .filter(|line| line.starts_with("stopped"))
+            .collect();
+        assert_eq!(stops, ["stopped: breakpoint at 6"]);
+        let infos: Vec<_> = out.lines().filter(|line| line.starts_with("ip ")).collect();
+        assert_eq!(
+            infos,
+            [
+                "ip 6, step 3, acc=1 a=0 b=0 c=0 d=0",
+                "ip 7, step 4, acc=2 a=0 b=0 c=0 d=0",
+            ]
+        );
+        assert!(out.contains("=>*    6  acc +1"));
+        // nothing runs after quit
+        assert!(out.trim_end().ends_with("(dbg) quit"));
+    }
+
+    #[test]
+    fn until_loop_stops_before_the_repeat() {
+        let out = session("until-loop\nback 100\ninfo");
+        assert!(out.contains("stopped: instruction 1 is about to run a second time"));
+        assert!(out.contains("step 7, acc=5 "));
+        assert!(out.contains("stopped: at the start of the program"));
+        assert!(out.contains("ip 0, step 0, acc=0 "));
+    }
+
+    #[test]
+    fn bad_commands_are_errors() {
+        let program = Program::parse(EXAMPLE, Rc::new(InstructionSet::default())).unwrap();
+        let mut debugger = Debugger::new(&program);
+        assert!(debugger.execute("").is_err());
+        assert!(debugger.execute("   ").is_err());
+        assert!(debugger.execute("break 99").is_err());
+        assert!(debugger.execute("watch q").is_err());
+    }
+}
@@ -0,0 +1,73 @@
+pub mod cfg;
+pub mod debugger;
+pub mod repair;
+pub mod trace;
+pub mod vm;
+
+use common::solution::{Answer, Solution};
+use std::rc::Rc;
+use vm::{ExitReason, InstructionSet, Machine, Program, RunOptions, Word};
+
+pub struct Day8;
+
+impl Solution for Day8 {
+    type Input = Program;
+    type Part1 = Answer<Word>;
+    type Part2 = Answer<Word>;
+
+    fn parse(src: &str) -> Result<Program, String> {
+        Program::parse(src, Rc::new(InstructionSet::default()))
+    }
+
+    // no answer if the program stops some other way than looping
+    fn part1(program: &Program) -> Answer<Word> {
+        Answer(part1(program))
+    }
+
+    // no answer if the program already finishes, or if no single swap fixes it
+    fn part2(program: &Program) -> Answer<Word> {
+        Answer(part2(program))
+    }
+}
+
+fn part1(program: &Program) -> Option<Word> {
+    let exit = Machine::new(program.set()).run(program, &RunOptions::default());
+    match exit.reason {
+        ExitReason::InfiniteLoop { .. } => Some(exit.state.accumulator()),
+        _ => None,
+    }
+}
+
+fn part2(program: &Program) -> Option<Word> {
+    let repairs = repair::single_flips(program).ok()?;
+    repairs.first().map(|repair| repair.accumulator)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6";
+
+    #[test]
+    fn solves_the_example() {
+        let program = Day8::parse(EXAMPLE).unwrap();
+        assert_eq!(Day8::part1(&program), Answer(Some(5)));
+        assert_eq!(Day8::part2(&program), Answer(Some(8)));
+    }
+
+    #[test]
+    fn no_answer_instead_of_a_panic() {
+        // finishes without looping
+        let program = Day8::parse("acc +1\nacc +2").unwrap();
+        assert_eq!(Day8::part1(&program), Answer(None));
+        assert_eq!(Day8::part2(&program), Answer(None));
+        // jumps out of the program
+        let program = Day8::parse("jmp -1").unwrap();
+        assert_eq!(Day8::part1(&program), Answer(None));
+        // loops, but no single swap gets it out
+        let program = Day8::parse("jmp +0\njmp -1").unwrap();
+        assert_eq!(Day8::part1(&program), Answer(Some(0)));
+        assert_eq!(Day8::part2(&program), Answer(None));
+    }
+}
@@ -0,0 +1,246 @@
+use aoc2020_day08::debugger::Debugger;
+use aoc2020_day08::repair::{self, Substitutions};
+use aoc2020_day08::trace::{self, Trace};
+use aoc2020_day08::vm::{
+    Flow, InstructionSet, LoopCheck, Machine, OperandKind, Program, RunOptions,
+};
+use aoc2020_day08::Day8;
+use common::cli::{self, Args, Cli};
+use std::fs::{self, File};
+use std::io::{self, BufReader, Write};
+
+const USAGE: &str = "usage:
+  aoc2020-day08
+  aoc2020-day08 run [--loop-check revisit|state] [--max-steps N] [--input FILE]
+  aoc2020-day08 opcodes
+  aoc2020-day08 debug [--script FILE] [--input FILE]
+  aoc2020-day08 repair [--input FILE]
+  aoc2020-day08 fix [--substitute OPCODES] [--input FILE]
+  aoc2020-day08 trace record [--format jsonl|binary] [--output FILE] [--repair]
+                             [--substitute OPCODES] [run options] [--input FILE]
+  aoc2020-day08 trace replay <trace-file>
+  aoc2020-day08 trace diff <trace-file> <trace-file>
+
+`run` runs a program and reports how it stopped, with the registers at the end. By
+default it stops the first time any instruction is about to run again, like the console;
+with `--loop-check state`, only when the whole machine repeats a state, which suits
+programs with conditional jumps, giving up after a million steps unless `--max-steps`
+says otherwise. `opcodes` lists the instructions programs can use.
+
+`debug` steps through a program, forwards and backwards, with breakpoints on instructions
+or opcodes and watchpoints on registers. It reads commands from the terminal, or from a
+script with `--script`; `help` lists them.
+
+`repair` lists every single jmp/nop swap that makes a looping program finish, with the
+accumulator it finishes with. `fix` finds the fewest changes that make it finish, swapping
+any of the comma-separated `--substitute` opcodes (jmp,nop by default) for another, and
+prints the repaired program. It works from the control-flow graph, which can't tell which
+way a conditional jump goes; when a program that branches doesn't follow the graph's
+answer, `fix` falls back to trying each single change, and gives up if none works.
+
+`trace record` runs a program like `run`, saving every step it takes with the accumulator
+before and after, as JSON lines or compact binary; with `--repair`, it first makes the
+fewest changes `fix` would. `trace replay` prints a saved trace of either format, and
+`trace diff` reports the first step at which two traces differ.";
+
+const CLI: Cli = Cli {
+    usage: USAGE,
+    switches: &["--repair"],
+    options: &[
+        ("--script", "a file"),
+        ("--substitute", "opcodes"),
+        ("--output", "a file"),
+        ("--format", "jsonl or binary"),
+        ("--loop-check", "revisit or state"),
+        ("--max-steps", "a number"),
+    ],
+};
+
+fn main() {
+    cli::main::<Day8>(2020, 8, &CLI, run);
+}
+
+fn run(command: &str, args: &Args) -> Result<(), String> {
+    match (command, &args.positional[..]) {
+        ("run", []) => run_program(args),
+        ("debug", []) => debug(args),
+        ("repair", []) => print_repairs(args),
+        ("fix", []) => print_fewest_changes(args),
+        ("trace", ["record"]) => record_trace(args),
+        ("trace", ["replay", file_name]) => {
+            let trace = load_trace(file_name)?;
+            for step in &trace.steps {
+                println!("{}", step);
+            }
+            println!("{} steps", trace.steps.len());
+            Ok(())
+        }
+        ("trace", ["diff", a, b]) => diff_traces(a, b),
+        ("opcodes", []) => {
+            print_opcodes();
+            Ok(())
+        }
+        _ => Err(args.unknown_command(command)),
+    }
+}
+
+fn run_options(args: &Args) -> Result<RunOptions, String> {
+    let loop_check = match args.choice("--loop-check", &["revisit", "state"])? {
+        Some("state") => LoopCheck::State,
+        _ => LoopCheck::Revisit,
+    };
+    let max_steps = args
+        .parsed("--max-steps")
+        .map_err(|e| args.usage_error(e))?;
+    Ok(RunOptions {
+        loop_check,
+        max_steps,
+        ..RunOptions::default()
+    })
+}
+
+fn substitutions(args: &Args, program: &Program) -> Result<Substitutions, String> {
+    let names: Vec<_> = args
+        .value("--substitute")
+        .unwrap_or("jmp,nop")
+        .split(',')
+        .map(str::trim)
+        .collect();
+    Substitutions::new(program.set(), &names)
+}
+
+fn run_program(args: &Args) -> Result<(), String> {
+    let options = run_options(args)?;
+    let program = args.load::<Day8>(2020, 8)?;
+    let exit = Machine::new(program.set()).run(&program, &options);
+    println!("{} after {} steps", exit.reason, exit.steps);
+    println!("ip = {}", exit.state.instruction_ptr);
+    for (name, value) in program.set().registers().iter().zip(exit.state.registers()) {
+        println!("{} = {}", name, value);
+    }
+    Ok(())
+}
+
+fn debug(args: &Args) -> Result<(), String> {
+    let program = args.load::<Day8>(2020, 8)?;
+    let mut debugger = Debugger::new(&program);
+    let stdout = io::stdout();
+    let result = match args.value("--script") {
+        Some(file_name) => {
+            let file = File::open(file_name).map_err(|e| format!("{}: {}", file_name, e))?;
+            debugger.repl(BufReader::new(file), stdout.lock(), true)
+        }
+        None => debugger.repl(io::stdin().lock(), stdout.lock(), false),
+    };
+    result.map_err(|e| e.to_string())
+}
+
+fn print_repairs(args: &Args) -> Result<(), String> {
+    let program = args.load::<Day8>(2020, 8)?;
+    let repairs = repair::single_flips(&program)?;
+    for repair in &repairs {
+        let change = &repair.changes[0];
+        println!(
+            "{}: {} -> {}, acc = {}",
+            change.at,
+            program.set().format(&change.from),
+            program.set().format(&change.to),
+            repair.accumulator
+        );
+    }
+    if repairs.is_empty() {
+        return Err("No single swap makes the program finish".into());
+    }
+    Ok(())
+}
+
+fn print_fewest_changes(args: &Args) -> Result<(), String> {
+    let program = args.load::<Day8>(2020, 8)?;
+    let substitutions = substitutions(args, &program)?;
+    let repair = repair::fewest_changes(&program, &substitutions)?;
+    println!("{} change(s)", repair.changes.len());
+    for change in &repair.changes {
+        println!(
+            "{}: {} -> {}",
+            change.at,
+            program.set().format(&change.from),
+            program.set().format(&change.to)
+        );
+    }
+    println!();
+    print!("{}", repair.program);
+    println!();
+    println!("acc = {}", repair.accumulator);
+    Ok(())
+}
+
+fn record_trace(args: &Args) -> Result<(), String> {
+    let run = RunOptions {
+        record: true,
+        ..run_options(args)?
+    };
+    let binary = args.choice("--format", &["jsonl", "binary"])? == Some("binary");
+    let mut program = args.load::<Day8>(2020, 8)?;
+    if args.switch("--repair") {
+        let substitutions = substitutions(args, &program)?;
+        program = repair::fewest_changes(&program, &substitutions)?.program;
+    }
+    let exit = Machine::new(program.set()).run(&program, &run);
+    let trace = exit.trace.unwrap();
+    let bytes = if binary {
+        trace.to_binary()
+    } else {
+        trace.to_jsonl().into_bytes()
+    };
+    match args.value("--output") {
+        Some(file_name) => {
+            fs::write(file_name, bytes).map_err(|e| format!("{}: {}", file_name, e))?
+        }
+        None => io::stdout().write_all(&bytes).map_err(|e| e.to_string())?,
+    }
+    eprintln!("{} after {} steps", exit.reason, exit.steps);
+    Ok(())
+}
+
+fn load_trace(file_name: &str) -> Result<Trace, String> {
+    let bytes = fs::read(file_name).map_err(|e| format!("{}: {}", file_name, e))?;
+    Trace::from_bytes(&bytes).map_err(|e| format!("{}: {}", file_name, e))
+}
+
+fn diff_traces(a: &str, b: &str) -> Result<(), String> {
+    let (trace_a, trace_b) = (load_trace(a)?, load_trace(b)?);
+    let divergence = match trace::diff(&trace_a, &trace_b) {
+        Some(divergence) => divergence,
+        None => {
+            println!("The traces are the same ({} steps)", trace_a.steps.len());
+            return Ok(());
+        }
+    };
+    println!("The traces diverge at step {}", divergence.step);
+    for (file_name, step) in [(a, divergence.a), (b, divergence.b)] {
+        match step {
+            Some(step) => println!("{}: {}", file_name, step),
+            None => println!("{}: (ended)", file_name),
+        }
+    }
+    Ok(())
+}
+
+fn print_opcodes() {
+    for def in InstructionSet::default().opcodes() {
+        let operands: Vec<_> = def
+            .operands
+            .iter()
+            .map(|kind| match kind {
+                OperandKind::Value => "<value>",
+                OperandKind::Register => "<reg>",
+            })
+            .collect();
+        let flow = match def.flow {
+            Flow::Next => String::new(),
+            Flow::Jump(idx) => format!(" (jumps by operand {})", idx + 1),
+            Flow::Branch(idx) => format!(" (may jump by operand {})", idx + 1),
+        };
+        println!("{} {}{}", def.name, operands.join(" "), flow);
+    }
+}
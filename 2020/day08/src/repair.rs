@@ -0,0 +1,353 @@
+use crate::cfg::{self, ControlFlow};
+use crate::vm::{
+    ExitReason, Instruction, InstructionSet, LoopCheck, Machine, Opcode, Program, RunOptions, Word,
+    STATE_STEP_LIMIT,
+};
+use std::collections::{HashSet, VecDeque};
+
+pub struct Change {
+    pub at: usize,
+    pub from: Instruction,
+    pub to: Instruction,
+}
+
+/// Changes that make a program finish, with the changed program and the accumulator it
+/// finishes with.
+pub struct Repair {
+    pub changes: Vec<Change>,
+    pub program: Program,
+    pub accumulator: Word,
+}
+
+/// Opcodes that can stand in for each other when repairing a program. Any of them can be
+/// swapped for another that takes the same kinds of operand, keeping the operands.
+pub struct Substitutions {
+    opcodes: Vec<Opcode>,
+}
+
+impl Substitutions {
+    pub fn new(set: &InstructionSet, names: &[&str]) -> Result<Substitutions, String> {
+        let opcodes = names
+            .iter()
+            .map(|name| set.opcode(name).ok_or(format!("Unknown opcode: {}", name)))
+            .collect::<Result<_, _>>()?;
+        Ok(Substitutions { opcodes })
+    }
+
+    // the console's corruption, a jmp for a nop or the other way round
+    pub fn jmp_nop(set: &InstructionSet) -> Result<Substitutions, String> {
+        Substitutions::new(set, &["jmp", "nop"])
+    }
+
+    fn alternatives(&self, set: &InstructionSet, instruction: &Instruction) -> Vec<Instruction> {
+        if !self.opcodes.contains(&instruction.opcode) {
+            return vec![];
+        }
+        let operands = &set.def(instruction.opcode).operands;
+        self.opcodes
+            .iter()
+            .filter(|&&opcode| {
+                opcode != instruction.opcode && set.def(opcode).operands == *operands
+            })
+            .map(|&opcode| Instruction {
+                opcode,
+                operands: instruction.operands.clone(),
+            })
+            .collect()
+    }
+}
+
+/// Every single `jmp`/`nop` swap that stops a program looping.
+///
+/// Only an instruction the broken run reaches can be at fault, and swapping it can only
+/// fix the program if control then goes somewhere that can reach the end. Checking that
+/// for every swap together is one linear pass over the control-flow graph. The graph
+/// ignores the registers, though, so each swap that passes is run to make sure. A program
+/// that can't branch usually has just one.
+pub fn single_flips(program: &Program) -> Result<Vec<Repair>, String> {
+    single_changes(program, &Substitutions::jmp_nop(program.set())?)
+}
+
+// every single substitution that makes the program finish, found as `single_flips` finds
+// swaps
+fn single_changes(program: &Program, substitutions: &Substitutions) -> Result<Vec<Repair>, String> {
+    let flow = ControlFlow::new(program);
+    let mut repairs = vec![];
+    for at in broken_run(program)? {
+        let from = &program.instructions()[at];
+        for to in substitutions.alternatives(program.set(), from) {
+            let fixed = cfg::jumps_by_register(program.set(), &to)
+                || cfg::successors(program.set(), program.len(), at, &to)
+                    .into_iter()
+                    .any(|next| flow.reaches_end(next));
+            if fixed {
+                let change = Change {
+                    at,
+                    from: from.clone(),
+                    to,
+                };
+                if let Ok(repair) = apply(program, vec![change]) {
+                    repairs.push(repair);
+                }
+            }
+        }
+    }
+    Ok(repairs)
+}
+
+/// The fewest substitutions that make a program finish.
+///
+/// Changing an instruction costs one and following it as it is costs nothing, so a
+/// cheapest path from the first instruction to the end of the control-flow graph, found
+/// with a 0-1 BFS, is a cheapest repair. A cheapest path never visits an instruction
+/// twice, and without branches the repaired program follows it exactly.
+///
+/// The graph can't tell which way a branch goes, though, so a program that branches may
+/// not follow the path once repaired. The result is run to make sure, and if it doesn't
+/// finish, any single change that does is still a fewest, since the program doesn't finish
+/// as it is. Past that it would take a search over every combination of changes, so a
+/// branching program that needs more than one change the graph gets wrong is an error.
+pub fn fewest_changes(program: &Program, substitutions: &Substitutions) -> Result<Repair, String> {
+    let (set, len) = (program.set(), program.len());
+    let mut cost = vec![usize::MAX; len + 1];
+    // how each instruction was first reached at its cost, and the change made on the way
+    let mut came_from: Vec<Option<(usize, Option<Instruction>)>> = vec![None; len + 1];
+    let mut queue = VecDeque::from([0]);
+    cost[0] = 0;
+    while let Some(idx) = queue.pop_front() {
+        if idx == len {
+            break;
+        }
+        let instruction = &program.instructions()[idx];
+        let kept = cfg::successors(set, len, idx, instruction)
+            .into_iter()
+            .map(|next| (next, None));
+        let changed = substitutions
+            .alternatives(set, instruction)
+            .into_iter()
+            .flat_map(|to| {
+                cfg::successors(set, len, idx, &to)
+                    .into_iter()
+                    .map(move |next| (next, Some(to.clone())))
+            });
+        for (next, change) in kept.chain(changed) {
+            let step = change.is_some() as usize;
+            if cost[idx] + step < cost[next] {
+                cost[next] = cost[idx] + step;
+                came_from[next] = Some((idx, change));
+                if step == 0 {
+                    queue.push_front(next);
+                } else {
+                    queue.push_back(next);
+                }
+            }
+        }
+    }
+    if cost[len] == usize::MAX {
+        return Err("No substitutions make the program finish".into());
+    }
+
+    let mut changes = vec![];
+    let mut idx = len;
+    while let Some((prev, change)) = came_from[idx].take() {
+        if let Some(to) = change {
+            changes.push(Change {
+                at: prev,
+                from: program.instructions()[prev].clone(),
+                to,
+            });
+        }
+        idx = prev;
+    }
+    changes.reverse();
+
+    let cheapest = apply(program, changes);
+    if cheapest.is_ok() || cfg::is_static(program) {
+        return cheapest;
+    }
+    match single_changes(program, substitutions)?.into_iter().next() {
+        Some(repair) => Ok(repair),
+        None => Err(format!(
+            "{}, and no single change fixes it; the program branches, so fewer changes than \
+             the control-flow graph suggests may not be enough",
+            cheapest.err().unwrap()
+        )),
+    }
+}
+
+fn apply(program: &Program, changes: Vec<Change>) -> Result<Repair, String> {
+    let mut repaired = program.clone();
+    for change in &changes {
+        repaired.replace(change.at, change.to.clone());
+    }
+    let exit = Machine::new(repaired.set()).run(&repaired, &check_options(&repaired));
+    if exit.reason != ExitReason::EndOfScript {
+        return Err(format!(
+            "The repaired program doesn't finish: {}",
+            exit.reason
+        ));
+    }
+    Ok(Repair {
+        changes,
+        program: repaired,
+        accumulator: exit.state.accumulator(),
+    })
+}
+
+// The console's rule is exact for a program that always takes the same path, but one that
+// branches can run an instruction again and still finish.
+fn check_options(program: &Program) -> RunOptions {
+    if cfg::is_static(program) {
+        RunOptions::default()
+    } else {
+        RunOptions {
+            loop_check: LoopCheck::State,
+            max_steps: Some(STATE_STEP_LIMIT),
+            ..RunOptions::default()
+        }
+    }
+}
+
+// the instructions the program reaches, in the order it first reaches them, before it
+// stops without finishing
+fn broken_run(program: &Program) -> Result<Vec<usize>, String> {
+    let options = check_options(program);
+    let mut seen = vec![false; program.len()];
+    let mut states = HashSet::new();
+    let mut path = vec![];
+    let mut machine = Machine::new(program.set());
+    for _ in 0..options.max_steps.unwrap_or(usize::MAX) {
+        let at = machine.instruction_ptr;
+        if at == program.len() {
+            return Err("The program already finishes".into());
+        }
+        if at > program.len() {
+            break;
+        }
+        let repeated = match options.loop_check {
+            LoopCheck::Revisit => seen[at],
+            LoopCheck::State => !states.insert(machine.clone()),
+        };
+        if repeated {
+            break;
+        }
+        if !std::mem::replace(&mut seen[at], true) {
+            path.push(at);
+        }
+        if machine.step(program).is_err() {
+            break;
+        }
+    }
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+
+    fn program(src: &str) -> Program {
+        Program::parse(src, Rc::new(InstructionSet::default())).unwrap()
+    }
+
+    fn changed(repair: &Repair) -> Vec<usize> {
+        repair.changes.iter().map(|change| change.at).collect()
+    }
+
+    #[test]
+    fn single_flips_finds_every_fix() {
+        let repairs = single_flips(&program("nop +3\njmp +0\nacc +5")).unwrap();
+        let fixed: Vec<_> = repairs.iter().flat_map(changed).collect();
+        assert_eq!(fixed, [0, 1]);
+        assert_eq!(repairs[0].accumulator, 0);
+        assert_eq!(repairs[1].accumulator, 5);
+
+        let console =
+            program("nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6");
+        let repairs = single_flips(&console).unwrap();
+        assert_eq!(repairs.len(), 1);
+        assert_eq!(changed(&repairs[0]), [7]);
+        assert_eq!(console.set().format(&repairs[0].changes[0].to), "nop -4");
+        assert_eq!(repairs[0].program.format(7), "nop -4");
+        assert_eq!(repairs[0].accumulator, 8);
+    }
+
+    #[test]
+    fn single_flips_follows_a_counted_loop() {
+        // the loop runs instruction 1 three times before the broken jmp sends it back
+        let program = program("set a 3\nacc +1\nadd a -1\njnz a -2\njmp -4\nacc +100");
+        let repairs = single_flips(&program).unwrap();
+        assert_eq!(repairs.len(), 1);
+        assert_eq!(changed(&repairs[0]), [4]);
+        assert_eq!(repairs[0].accumulator, 103);
+    }
+
+    #[test]
+    fn single_flips_needs_a_broken_program() {
+        assert_eq!(
+            single_flips(&program("acc +1")).err().unwrap(),
+            "The program already finishes"
+        );
+        assert!(single_flips(&program("jmp +0\njmp -1")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn fewest_changes_finds_the_console_fix() {
+        let program =
+            program("nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6");
+        let substitutions = Substitutions::jmp_nop(program.set()).unwrap();
+        let repair = fewest_changes(&program, &substitutions).unwrap();
+        assert_eq!(changed(&repair), [7]);
+        assert_eq!(repair.accumulator, 8);
+    }
+
+    #[test]
+    fn fewest_changes_can_take_several() {
+        let both = program("jmp +0\nacc +1\njmp +0\nacc +2");
+        let substitutions = Substitutions::jmp_nop(both.set()).unwrap();
+        let repair = fewest_changes(&both, &substitutions).unwrap();
+        assert_eq!(changed(&repair), [0, 2]);
+        assert_eq!(repair.accumulator, 3);
+
+        // a jmp with nothing to stand in for it stays a jmp
+        let stuck = program("acc +1\njmp -1");
+        let substitutions = Substitutions::new(stuck.set(), &["jmp", "jz"]).unwrap();
+        assert_eq!(
+            fewest_changes(&stuck, &substitutions).err().unwrap(),
+            "No substitutions make the program finish"
+        );
+        assert_eq!(
+            Substitutions::new(stuck.set(), &["jmp", "hop"])
+                .err()
+                .unwrap(),
+            "Unknown opcode: hop"
+        );
+    }
+
+    #[test]
+    fn fewest_changes_accepts_a_counted_loop() {
+        let program = program("set a 3\nacc +1\nadd a -1\njnz a -2\njmp -4\nacc +100");
+        let substitutions = Substitutions::jmp_nop(program.set()).unwrap();
+        let repair = fewest_changes(&program, &substitutions).unwrap();
+        assert_eq!(changed(&repair), [4]);
+        assert_eq!(repair.accumulator, 103);
+    }
+
+    #[test]
+    fn fewest_changes_on_branches_the_graph_gets_wrong() {
+        // the graph goes 0, 1, end with no changes, but a is 0 so the jz is always taken
+        let one = program("jz a +2\njmp +2\njmp +0");
+        let substitutions = Substitutions::jmp_nop(one.set()).unwrap();
+        let repair = fewest_changes(&one, &substitutions).unwrap();
+        assert_eq!(changed(&repair), [2]);
+
+        // the same, but it takes two changes, which the fallback doesn't look for
+        let two = program("jz a +3\nnop +0\njmp +3\njmp +0\njmp +0");
+        assert_eq!(
+            fewest_changes(&two, &substitutions).err().unwrap(),
+            "The repaired program doesn't finish: looped back to instruction 3, and no \
+             single change fixes it; the program branches, so fewer changes than the \
+             control-flow graph suggests may not be enough"
+        );
+    }
+}
@@ -0,0 +1,404 @@
+use crate::vm::Word;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+
+// starts every binary trace, followed by a format version
+const MAGIC: &[u8] = b"D8TR";
+const VERSION: u8 = 1;
+
+/// One instruction run: which one, and the accumulator either side of it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TraceStep {
+    pub step: usize,
+    pub ip: usize,
+    pub instruction: String,
+    pub acc_before: Word,
+    pub acc_after: Word,
+}
+
+/// Every instruction a run went through, in order. Traces are saved either as JSON
+/// lines, one step per line, or in a compact binary form:
+///
+///   "D8TR" version
+///   count, then each distinct instruction as a length and its UTF-8 text
+///   count, then each step as step, ip, instruction index, acc before, acc after
+///
+/// where every number is a LEB128 varint, zigzag-encoded for the accumulator.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Trace {
+    pub steps: Vec<TraceStep>,
+}
+
+/// Where two traces first differ. A side is None if that trace had already ended.
+pub struct Divergence {
+    pub step: usize,
+    pub a: Option<TraceStep>,
+    pub b: Option<TraceStep>,
+}
+
+impl Display for TraceStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:>6}  {:>5}  {:<14} acc {} -> {}",
+            self.step, self.ip, self.instruction, self.acc_before, self.acc_after
+        )
+    }
+}
+
+impl Trace {
+    pub fn to_jsonl(&self) -> String {
+        self.steps
+            .iter()
+            .map(|step| serde_json::to_string(step).unwrap() + "\n")
+            .collect()
+    }
+
+    pub fn to_binary(&self) -> Vec<u8> {
+        let mut texts: Vec<&str> = vec![];
+        let mut indices = HashMap::new();
+        for step in &self.steps {
+            indices.entry(&step.instruction[..]).or_insert_with(|| {
+                texts.push(&step.instruction);
+                texts.len() - 1
+            });
+        }
+
+        let mut out = MAGIC.to_vec();
+        out.push(VERSION);
+        write_varint(&mut out, texts.len() as u64);
+        for text in &texts {
+            write_varint(&mut out, text.len() as u64);
+            out.extend_from_slice(text.as_bytes());
+        }
+        write_varint(&mut out, self.steps.len() as u64);
+        for step in &self.steps {
+            write_varint(&mut out, step.step as u64);
+            write_varint(&mut out, step.ip as u64);
+            write_varint(&mut out, indices[&step.instruction[..]] as u64);
+            write_varint(&mut out, zigzag(step.acc_before));
+            write_varint(&mut out, zigzag(step.acc_after));
+        }
+        out
+    }
+
+    // either format, told apart by the binary form's magic number
+    pub fn from_bytes(bytes: &[u8]) -> Result<Trace, String> {
+        if bytes.starts_with(MAGIC) {
+            Trace::from_binary(bytes)
+        } else {
+            let src = std::str::from_utf8(bytes)
+                .map_err(|_| "Not a trace: neither binary nor UTF-8 text")?;
+            Trace::from_jsonl(src)
+        }
+    }
+
+    pub fn from_jsonl(src: &str) -> Result<Trace, String> {
+        let steps = src
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(idx, line)| {
+                serde_json::from_str(line).map_err(|e| format!("line {}: {}", idx + 1, e))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Trace { steps })
+    }
+
+    fn from_binary(bytes: &[u8]) -> Result<Trace, String> {
+        let mut reader = Reader {
+            bytes,
+            offset: MAGIC.len(),
+        };
+        let version = reader.byte()?;
+        if version != VERSION {
+            return Err(format!("Unsupported trace version {}", version));
+        }
+        let texts = (0..reader.varint()?)
+            .map(|_| {
+                let len = reader.varint()? as usize;
+                let text = reader.take(len)?;
+                String::from_utf8(text.to_vec())
+                    .map_err(|_| format!("offset {}: instruction is not UTF-8", reader.offset))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let steps = (0..reader.varint()?)
+            .map(|_| {
+                let step = reader.varint()? as usize;
+                let ip = reader.varint()? as usize;
+                let text = reader.varint()? as usize;
+                let instruction = texts
+                    .get(text)
+                    .ok_or(format!("offset {}: no instruction {}", reader.offset, text))?
+                    .clone();
+                Ok(TraceStep {
+                    step,
+                    ip,
+                    instruction,
+                    acc_before: unzigzag(reader.varint()?),
+                    acc_after: unzigzag(reader.varint()?),
+                })
+            })
+            .collect::<Result<_, String>>()?;
+        if reader.offset != bytes.len() {
+            return Err(format!("offset {}: trailing bytes", reader.offset));
+        }
+        Ok(Trace { steps })
+    }
+}
+
+// None if the traces are the same
+pub fn diff(a: &Trace, b: &Trace) -> Option<Divergence> {
+    let len = a.steps.len().max(b.steps.len());
+    (0..len)
+        .find(|&idx| a.steps.get(idx) != b.steps.get(idx))
+        .map(|idx| Divergence {
+            step: idx,
+            a: a.steps.get(idx).cloned(),
+            b: b.steps.get(idx).cloned(),
+        })
+}
+
+fn write_varint(out: &mut Vec<u8>, mut n: u64) {
+    while n >= 0x80 {
+        out.push(n as u8 | 0x80);
+        n >>= 7;
+    }
+    out.push(n as u8);
+}
+
+fn zigzag(n: Word) -> u64 {
+    let n = n as i64;
+    ((n << 1) ^ (n >> 63)) as u64
+}
+
+fn unzigzag(n: u64) -> Word {
+    ((n >> 1) as i64 ^ -((n & 1) as i64)) as Word
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let taken = (self.offset.checked_add(len))
+            .and_then(|end| self.bytes.get(self.offset..end))
+            .ok_or(format!("offset {}: unexpected end of trace", self.offset))?;
+        self.offset += len;
+        Ok(taken)
+    }
+
+    fn byte(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn varint(&mut self) -> Result<u64, String> {
+        let mut n = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            // the tenth byte only has room for the top bit
+            if shift == 63 && byte & 0x7e != 0 {
+                break;
+            }
+            n |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(n);
+            }
+        }
+        Err(format!("offset {}: varint too long", self.offset))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(
+        step: usize,
+        ip: usize,
+        instruction: &str,
+        acc_before: Word,
+        acc_after: Word,
+    ) -> TraceStep {
+        TraceStep {
+            step,
+            ip,
+            instruction: instruction.into(),
+            acc_before,
+            acc_after,
+        }
+    }
+
+    fn example() -> Trace {
+        Trace {
+            steps: vec![
+                step(0, 0, "acc -1", 0, -1),
+                step(1, 1, "jmp +2", -1, -1),
+                step(2, 3, "acc +1", -1, 0),
+                step(3, 4, "acc -1", 0, -1),
+                step(4, 5, "acc +300", -1, 299),
+                step(5, 6, "acc -9223372036854775807", 299, Word::MIN),
+                step(6, 7, "acc +0", Word::MIN, Word::MAX),
+            ],
+        }
+    }
+
+    #[test]
+    fn round_trips_both_formats() {
+        let trace = example();
+        let binary = trace.to_binary();
+        assert!(binary.starts_with(b"D8TR\x01"));
+        assert_eq!(Trace::from_bytes(&binary).unwrap(), trace);
+
+        let jsonl = trace.to_jsonl();
+        assert_eq!(jsonl.lines().count(), trace.steps.len());
+        assert_eq!(
+            jsonl.lines().next().unwrap(),
+            r#"{"step":0,"ip":0,"instruction":"acc -1","acc_before":0,"acc_after":-1}"#
+        );
+        assert_eq!(Trace::from_bytes(jsonl.as_bytes()).unwrap(), trace);
+        assert_eq!(
+            Trace::from_jsonl(&jsonl).unwrap(),
+            Trace::from_binary(&binary).unwrap()
+        );
+        assert_eq!(Trace::from_bytes(b"").unwrap(), Trace::default());
+        assert_eq!(
+            Trace::from_bytes(&Trace::default().to_binary()).unwrap(),
+            Trace::default()
+        );
+    }
+
+    #[test]
+    fn binary_shares_repeated_instructions() {
+        let trace = example();
+        // acc -1 is written once and referred to by index after that
+        let binary = trace.to_binary();
+        let text = b"acc -1";
+        let count = binary.windows(text.len()).filter(|w| w == text).count();
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn zigzag_keeps_small_accumulators_small() {
+        for (n, encoded) in [(0, 0), (-1, 1), (1, 2), (-2, 3), (63, 126), (-64, 127)] {
+            assert_eq!(zigzag(n), encoded);
+            assert_eq!(unzigzag(encoded), n);
+        }
+        for n in [Word::MIN, Word::MIN + 1, Word::MAX, -300, 300] {
+            assert_eq!(unzigzag(zigzag(n)), n);
+        }
+        let mut out = vec![];
+        write_varint(&mut out, zigzag(-64));
+        assert_eq!(out, [0x7f]);
+        write_varint(&mut out, zigzag(64));
+        assert_eq!(out, [0x7f, 0x80, 0x01]);
+    }
+
+    #[test]
+    fn rejects_truncated_traces() {
+        let binary = example().to_binary();
+        for len in MAGIC.len()..binary.len() {
+            assert!(
+                Trace::from_bytes(&binary[..len]).is_err(),
+                "accepted {} of {} bytes",
+                len,
+                binary.len()
+            );
+        }
+        assert_eq!(
+            Trace::from_bytes(b"D8TR").unwrap_err(),
+            "offset 4: unexpected end of trace"
+        );
+    }
+
+    #[test]
+    fn rejects_other_versions_and_trailing_bytes() {
+        assert_eq!(
+            Trace::from_bytes(b"D8TR\x02\x00\x00").unwrap_err(),
+            "Unsupported trace version 2"
+        );
+        let mut binary = example().to_binary();
+        let len = binary.len();
+        binary.push(0);
+        assert_eq!(
+            Trace::from_bytes(&binary).unwrap_err(),
+            format!("offset {}: trailing bytes", len)
+        );
+    }
+
+    #[test]
+    fn rejects_bad_varints_and_references() {
+        let mut too_long = b"D8TR\x01".to_vec();
+        too_long.extend([0x80; 10]);
+        too_long.push(0);
+        assert_eq!(
+            Trace::from_bytes(&too_long).unwrap_err(),
+            "offset 15: varint too long"
+        );
+        let mut too_big = b"D8TR\x01".to_vec();
+        too_big.extend([0xff; 9]);
+        too_big.push(0x02);
+        assert_eq!(
+            Trace::from_bytes(&too_big).unwrap_err(),
+            "offset 15: varint too long"
+        );
+        let mut largest = vec![];
+        write_varint(&mut largest, u64::MAX);
+        assert_eq!(largest.len(), 10);
+        let mut reader = Reader {
+            bytes: &largest,
+            offset: 0,
+        };
+        assert_eq!(reader.varint().unwrap(), u64::MAX);
+        // one step that refers to instruction 1 of 1
+        let dangling = b"D8TR\x01\x01\x03nop\x01\x00\x00\x01\x00\x00";
+        assert_eq!(
+            Trace::from_bytes(dangling).unwrap_err(),
+            "offset 14: no instruction 1"
+        );
+        assert_eq!(
+            Trace::from_bytes(b"D8TR\x01\x01\x02\xff\xfe\x00").unwrap_err(),
+            "offset 9: instruction is not UTF-8"
+        );
+        assert_eq!(
+            Trace::from_bytes(b"\xff\xfe").unwrap_err(),
+            "Not a trace: neither binary nor UTF-8 text"
+        );
+        assert!(Trace::from_bytes(b"{\"step\":0}\n")
+            .unwrap_err()
+            .starts_with("line 1: "));
+    }
+
+    #[test]
+    fn diffs_report_the_first_difference() {
+        let trace = example();
+        assert!(diff(&trace, &trace).is_none());
+
+        let mut changed = trace.clone();
+        changed.steps[3].acc_after = 7;
+        changed.steps[5].ip = 0;
+        let divergence = diff(&trace, &changed).unwrap();
+        assert_eq!(divergence.step, 3);
+        assert_eq!(divergence.a, Some(trace.steps[3].clone()));
+        assert_eq!(divergence.b, Some(changed.steps[3].clone()));
+    }
+
+    #[test]
+    fn diffs_when_one_trace_is_a_prefix_of_the_other() {
+        let trace = example();
+        let prefix = Trace {
+            steps: trace.steps[..4].to_vec(),
+        };
+        let divergence = diff(&prefix, &trace).unwrap();
+        assert_eq!(divergence.step, 4);
+        assert_eq!(divergence.a, None);
+        assert_eq!(divergence.b, Some(trace.steps[4].clone()));
+
+        let divergence = diff(&trace, &Trace::default()).unwrap();
+        assert_eq!(divergence.step, 0);
+        assert_eq!(divergence.b, None);
+    }
+}
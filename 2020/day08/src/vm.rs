@@ -0,0 +1,616 @@
+use crate::trace::{Trace, TraceStep};
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Display};
+use std::rc::Rc;
+use std::str::FromStr;
+
+pub type Word = isize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Register(pub usize);
+
+// always the first register of an instruction set
+pub const ACCUMULATOR: Register = Register(0);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Operand {
+    Immediate(Word),
+    Register(Register),
+}
+
+/// What an opcode accepts in each operand position: any value, or only a register it
+/// can write to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperandKind {
+    Value,
+    Register,
+}
+
+/// Where control can go after an opcode runs, for analysing programs without running
+/// them. Jumps are relative, by the value of the given operand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flow {
+    Next,
+    Jump(usize),
+    // either the next instruction or the jump
+    Branch(usize),
+}
+
+// runs an instruction, returning the relative jump to take, if any
+pub type Exec = fn(&mut Machine, &[Operand]) -> Result<Option<Word>, String>;
+
+pub struct OpcodeDef {
+    pub name: String,
+    pub operands: Vec<OperandKind>,
+    pub flow: Flow,
+    pub exec: Exec,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Opcode(usize);
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Instruction {
+    pub opcode: Opcode,
+    pub operands: Vec<Operand>,
+}
+
+/// The opcodes and registers programs can use. The default set has the console's `acc`,
+/// `jmp` and `nop`, and extends it with registers a to d, arithmetic and conditional jumps:
+///
+///   add <reg> <value>    set <reg> <value>    mul <reg> <value>
+///   jz <value> <offset>  jnz <value> <offset> jgz <value> <offset>  jlz <value> <offset>
+///
+/// More opcodes can be registered before programs are parsed.
+pub struct InstructionSet {
+    opcodes: Vec<OpcodeDef>,
+    by_name: HashMap<String, Opcode>,
+    registers: Vec<String>,
+}
+
+impl InstructionSet {
+    // the first register is the accumulator
+    pub fn new(registers: &[&str]) -> Result<InstructionSet, String> {
+        if registers.is_empty() {
+            return Err("An instruction set needs at least one register".into());
+        }
+        let names: HashSet<_> = registers.iter().collect();
+        if names.len() != registers.len() {
+            return Err("Register names must be unique".into());
+        }
+        Ok(InstructionSet {
+            opcodes: vec![],
+            by_name: HashMap::new(),
+            registers: registers.iter().map(|name| name.to_string()).collect(),
+        })
+    }
+
+    pub fn register(&mut self, def: OpcodeDef) -> Result<Opcode, String> {
+        if self.by_name.contains_key(&def.name) {
+            return Err(format!("{} is already defined", def.name));
+        }
+        let jump_operand = match def.flow {
+            Flow::Next => None,
+            Flow::Jump(idx) | Flow::Branch(idx) => Some(idx),
+        };
+        if jump_operand.is_some_and(|idx| idx >= def.operands.len()) {
+            return Err(format!("{} jumps by an operand it doesn't have", def.name));
+        }
+        let opcode = Opcode(self.opcodes.len());
+        self.by_name.insert(def.name.clone(), opcode);
+        self.opcodes.push(def);
+        Ok(opcode)
+    }
+
+    pub fn opcode(&self, name: &str) -> Option<Opcode> {
+        self.by_name.get(name).copied()
+    }
+
+    pub fn def(&self, opcode: Opcode) -> &OpcodeDef {
+        &self.opcodes[opcode.0]
+    }
+
+    pub fn opcodes(&self) -> &[OpcodeDef] {
+        &self.opcodes
+    }
+
+    pub fn registers(&self) -> &[String] {
+        &self.registers
+    }
+
+    pub fn register_named(&self, name: &str) -> Option<Register> {
+        self.registers
+            .iter()
+            .position(|register| register == name)
+            .map(Register)
+    }
+
+    pub fn parse_instruction(&self, s: &str) -> Result<Instruction, String> {
+        let mut words = s.split_whitespace();
+        let name = words.next().ok_or("Expected an instruction")?;
+        let opcode = self
+            .opcode(name)
+            .ok_or(format!("Unrecognized instruction: {}", name))?;
+        let def = self.def(opcode);
+        let words: Vec<_> = words.collect();
+        if words.len() != def.operands.len() {
+            return Err(format!(
+                "{} takes {} operand(s) but was given {}",
+                name,
+                def.operands.len(),
+                words.len()
+            ));
+        }
+
+        let operands = words
+            .iter()
+            .zip(&def.operands)
+            .map(|(word, kind)| match (self.register_named(word), kind) {
+                (Some(register), _) => Ok(Operand::Register(register)),
+                (None, OperandKind::Register) => Err(format!(
+                    "{} expects a register but was given {}",
+                    name, word
+                )),
+                (None, OperandKind::Value) => parse_immediate(word).map(Operand::Immediate),
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Instruction { opcode, operands })
+    }
+
+    pub fn format(&self, instruction: &Instruction) -> String {
+        let mut s = self.def(instruction.opcode).name.clone();
+        for operand in &instruction.operands {
+            match operand {
+                Operand::Immediate(value) => s.push_str(&format!(" {:+}", value)),
+                Operand::Register(register) => {
+                    s.push(' ');
+                    s.push_str(&self.registers[register.0]);
+                }
+            }
+        }
+        s
+    }
+
+    fn builtin(&mut self, name: &str, operands: &[OperandKind], flow: Flow, exec: Exec) {
+        self.register(OpcodeDef {
+            name: name.into(),
+            operands: operands.to_vec(),
+            flow,
+            exec,
+        })
+        .unwrap();
+    }
+}
+
+impl Default for InstructionSet {
+    fn default() -> Self {
+        use OperandKind::*;
+        let mut set = InstructionSet::new(&["acc", "a", "b", "c", "d"]).unwrap();
+        set.builtin("acc", &[Value], Flow::Next, |m, ops| {
+            m.apply(ACCUMULATOR, m.read(ops[0]), Word::checked_add)
+        });
+        set.builtin("jmp", &[Value], Flow::Jump(0), |m, ops| {
+            Ok(Some(m.read(ops[0])))
+        });
+        set.builtin("nop", &[Value], Flow::Next, |_, _| Ok(None));
+        set.builtin("add", &[Register, Value], Flow::Next, |m, ops| {
+            m.apply(destination(ops[0]), m.read(ops[1]), Word::checked_add)
+        });
+        set.builtin("mul", &[Register, Value], Flow::Next, |m, ops| {
+            m.apply(destination(ops[0]), m.read(ops[1]), Word::checked_mul)
+        });
+        set.builtin("set", &[Register, Value], Flow::Next, |m, ops| {
+            m.apply(destination(ops[0]), m.read(ops[1]), |_, value| Some(value))
+        });
+        set.builtin("jz", &[Value, Value], Flow::Branch(1), |m, ops| {
+            Ok(Some(m.read(ops[1])).filter(|_| m.read(ops[0]) == 0))
+        });
+        set.builtin("jnz", &[Value, Value], Flow::Branch(1), |m, ops| {
+            Ok(Some(m.read(ops[1])).filter(|_| m.read(ops[0]) != 0))
+        });
+        set.builtin("jgz", &[Value, Value], Flow::Branch(1), |m, ops| {
+            Ok(Some(m.read(ops[1])).filter(|_| m.read(ops[0]) > 0))
+        });
+        set.builtin("jlz", &[Value, Value], Flow::Branch(1), |m, ops| {
+            Ok(Some(m.read(ops[1])).filter(|_| m.read(ops[0]) < 0))
+        });
+        set
+    }
+}
+
+// operand kinds are checked when parsing, so a register operand is always a register
+fn destination(operand: Operand) -> Register {
+    match operand {
+        Operand::Register(register) => register,
+        Operand::Immediate(_) => unreachable!(),
+    }
+}
+
+fn parse_immediate(word: &str) -> Result<Word, String> {
+    let digits = word.trim_start_matches(['+', '-']);
+    if digits.is_empty()
+        || !digits.bytes().all(|b| b.is_ascii_digit())
+        || word.len() - digits.len() > 1
+    {
+        return Err(format!("{} is neither a number nor a register", word));
+    }
+    Word::from_str(word.trim_start_matches('+'))
+        .map_err(|e| format!("{} is not a valid isize: {}", word, e))
+}
+
+/// A list of instructions, along with the set they were parsed against.
+#[derive(Clone)]
+pub struct Program {
+    set: Rc<InstructionSet>,
+    instructions: Vec<Instruction>,
+}
+
+impl Program {
+    // one instruction per line; blank lines are skipped
+    pub fn parse(src: &str, set: Rc<InstructionSet>) -> Result<Program, String> {
+        let instructions = src
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(idx, line)| {
+                set.parse_instruction(line)
+                    .map_err(|e| format!("line {}: {}", idx + 1, e))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Program { set, instructions })
+    }
+
+    pub fn set(&self) -> &InstructionSet {
+        &self.set
+    }
+
+    pub fn instructions(&self) -> &[Instruction] {
+        &self.instructions
+    }
+
+    pub fn replace(&mut self, idx: usize, instruction: Instruction) {
+        self.instructions[idx] = instruction;
+    }
+
+    pub fn len(&self) -> usize {
+        self.instructions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.instructions.is_empty()
+    }
+
+    pub fn format(&self, idx: usize) -> String {
+        self.set.format(&self.instructions[idx])
+    }
+}
+
+impl Display for Program {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (0..self.len()).try_for_each(|idx| writeln!(f, "{}", self.format(idx)))
+    }
+}
+
+/// How a run decides it's stuck. The console's rule is that a program loops as soon as
+/// any instruction is about to run a second time, which is only right for programs that
+/// can't branch; checking for a whole repeated state works for any program, but a program
+/// that counts forever never repeats one, so a state check without a step limit of its own
+/// stops at `STATE_STEP_LIMIT`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoopCheck {
+    Revisit,
+    State,
+}
+
+// every state seen is kept, so a state check can't be left to run forever
+pub const STATE_STEP_LIMIT: usize = 1_000_000;
+
+#[derive(Debug, Clone, Copy)]
+pub struct RunOptions {
+    pub loop_check: LoopCheck,
+    pub max_steps: Option<usize>,
+    // keep a trace of every instruction run
+    pub record: bool,
+}
+
+impl Default for RunOptions {
+    fn default() -> Self {
+        RunOptions {
+            loop_check: LoopCheck::Revisit,
+            max_steps: None,
+            record: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExitReason {
+    EndOfScript,
+    // the instruction about to run again
+    InfiniteLoop { at: usize },
+    // no target if it's past the largest Word
+    InvalidJump { from: usize, target: Option<Word> },
+    Fault { at: usize, error: String },
+    StepLimit,
+}
+
+impl Display for ExitReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExitReason::EndOfScript => write!(f, "ran off the end of the program"),
+            ExitReason::InfiniteLoop { at } => write!(f, "looped back to instruction {}", at),
+            ExitReason::InvalidJump {
+                from,
+                target: Some(target),
+            } => write!(
+                f,
+                "instruction {} jumped to {}, outside the program",
+                from, target
+            ),
+            ExitReason::InvalidJump { from, target: None } => {
+                write!(f, "instruction {} jumped past the largest address", from)
+            }
+            ExitReason::Fault { at, error } => write!(f, "instruction {} failed: {}", at, error),
+            ExitReason::StepLimit => write!(f, "hit the step limit"),
+        }
+    }
+}
+
+/// Why a run stopped, the machine as it was then, and how many instructions it ran.
+#[derive(Debug, Clone)]
+pub struct ExitCondition {
+    pub reason: ExitReason,
+    pub state: Machine,
+    pub steps: usize,
+    // only if the run was recorded
+    pub trace: Option<Trace>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Machine {
+    pub instruction_ptr: usize,
+    registers: Vec<Word>,
+}
+
+impl Machine {
+    pub fn new(set: &InstructionSet) -> Self {
+        Machine {
+            instruction_ptr: 0,
+            registers: vec![0; set.registers().len()],
+        }
+    }
+
+    pub fn accumulator(&self) -> Word {
+        self.registers[ACCUMULATOR.0]
+    }
+
+    pub fn registers(&self) -> &[Word] {
+        &self.registers
+    }
+
+    pub fn read(&self, operand: Operand) -> Word {
+        match operand {
+            Operand::Immediate(value) => value,
+            Operand::Register(register) => self.registers[register.0],
+        }
+    }
+
+    pub fn write(&mut self, register: Register, value: Word) {
+        self.registers[register.0] = value;
+    }
+
+    // combines a register with a value, failing on overflow
+    pub fn apply<F>(
+        &mut self,
+        register: Register,
+        value: Word,
+        op: F,
+    ) -> Result<Option<Word>, String>
+    where
+        F: Fn(Word, Word) -> Option<Word>,
+    {
+        let current = self.registers[register.0];
+        self.registers[register.0] =
+            op(current, value).ok_or(format!("overflow combining {} with {}", current, value))?;
+        Ok(None)
+    }
+
+    // Runs the instruction at the pointer, which must be inside the program. A jump past
+    // the end isn't an error until the next step, since landing just past it is how a
+    // program finishes.
+    pub fn step(&mut self, program: &Program) -> Result<(), ExitReason> {
+        let at = self.instruction_ptr;
+        let instruction = &program.instructions[at];
+        let def = program.set.def(instruction.opcode);
+        let jump = (def.exec)(self, &instruction.operands)
+            .map_err(|error| ExitReason::Fault { at, error })?;
+
+        let target = (at as Word).checked_add(jump.unwrap_or(1));
+        match target {
+            Some(target) if target >= 0 => self.instruction_ptr = target as usize,
+            _ => return Err(ExitReason::InvalidJump { from: at, target }),
+        }
+        Ok(())
+    }
+
+    pub fn run(mut self, program: &Program, options: &RunOptions) -> ExitCondition {
+        let mut visited = vec![false; program.len()];
+        let mut states = HashSet::new();
+        let mut steps = 0;
+        let mut last = 0;
+        let mut trace = Trace::default();
+        let max_steps = match options.loop_check {
+            LoopCheck::Revisit => options.max_steps,
+            LoopCheck::State => Some(options.max_steps.unwrap_or(STATE_STEP_LIMIT)),
+        };
+
+        let reason = loop {
+            let at = self.instruction_ptr;
+            if at == program.len() {
+                break ExitReason::EndOfScript;
+            } else if at > program.len() {
+                break ExitReason::InvalidJump {
+                    from: last,
+                    target: Some(at as Word),
+                };
+            }
+            let repeated = match options.loop_check {
+                LoopCheck::Revisit => std::mem::replace(&mut visited[at], true),
+                LoopCheck::State => !states.insert(self.clone()),
+            };
+            if repeated {
+                break ExitReason::InfiniteLoop { at };
+            }
+            if max_steps.is_some_and(|max| steps >= max) {
+                break ExitReason::StepLimit;
+            }
+            let acc_before = self.accumulator();
+            if let Err(reason) = self.step(program) {
+                common::event!(Warn, "program stopped", reason = reason.to_string());
+                break reason;
+            }
+            if options.record {
+                trace.steps.push(TraceStep {
+                    step: steps,
+                    ip: at,
+                    instruction: program.format(at),
+                    acc_before,
+                    acc_after: self.accumulator(),
+                });
+            }
+            steps += 1;
+            last = at;
+        };
+
+        ExitCondition {
+            reason,
+            state: self,
+            steps,
+            trace: Some(trace).filter(|_| options.record),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(src: &str, options: &RunOptions) -> ExitCondition {
+        let program = Program::parse(src, Rc::new(InstructionSet::default())).unwrap();
+        Machine::new(program.set()).run(&program, options)
+    }
+
+    #[test]
+    fn jumps_that_overflow_are_invalid() {
+        let exit = run("nop +0\njmp +9223372036854775807", &RunOptions::default());
+        assert_eq!(
+            exit.reason,
+            ExitReason::InvalidJump {
+                from: 1,
+                target: None
+            }
+        );
+        assert_eq!(
+            exit.reason.to_string(),
+            "instruction 1 jumped past the largest address"
+        );
+        assert_eq!(exit.steps, 1);
+    }
+
+    #[test]
+    fn jumps_outside_the_program_are_invalid() {
+        let before = run("nop +0\njmp -2", &RunOptions::default());
+        assert_eq!(
+            before.reason,
+            ExitReason::InvalidJump {
+                from: 1,
+                target: Some(-1)
+            }
+        );
+        let after = run("jmp +3\nnop +0", &RunOptions::default());
+        assert_eq!(
+            after.reason,
+            ExitReason::InvalidJump {
+                from: 0,
+                target: Some(3)
+            }
+        );
+        assert_eq!(
+            after.reason.to_string(),
+            "instruction 0 jumped to 3, outside the program"
+        );
+        // landing just past the end is how a program finishes
+        let end = run("acc +1\njmp +2\nacc +5", &RunOptions::default());
+        assert_eq!(end.reason, ExitReason::EndOfScript);
+        assert_eq!(end.state.accumulator(), 1);
+    }
+
+    #[test]
+    fn loop_checks() {
+        // counts a down to zero, revisiting the loop but never repeating a state
+        let src = "set a +3\nadd a -1\njnz a -1\nacc +7";
+        let revisit = run(src, &RunOptions::default());
+        assert_eq!(revisit.reason, ExitReason::InfiniteLoop { at: 1 });
+        let state = RunOptions {
+            loop_check: LoopCheck::State,
+            ..RunOptions::default()
+        };
+        let finished = run(src, &state);
+        assert_eq!(finished.reason, ExitReason::EndOfScript);
+        assert_eq!(finished.state.accumulator(), 7);
+        assert_eq!(finished.state.registers()[1], 0);
+        assert_eq!(finished.steps, 8);
+
+        assert_eq!(
+            run("jmp +0", &state).reason,
+            ExitReason::InfiniteLoop { at: 0 }
+        );
+        // counting forever never repeats a state, so the default limit stops it
+        let counting = run("acc +1\njmp -1", &state);
+        assert_eq!(counting.reason, ExitReason::StepLimit);
+        assert_eq!(counting.steps, STATE_STEP_LIMIT);
+        let limited = RunOptions {
+            max_steps: Some(5),
+            ..state
+        };
+        assert_eq!(run("acc +1\njmp -1", &limited).steps, 5);
+    }
+
+    #[test]
+    fn arithmetic_overflow_is_a_fault() {
+        let exit = run(
+            "set a +9223372036854775807\nadd a +1",
+            &RunOptions::default(),
+        );
+        assert_eq!(
+            exit.reason,
+            ExitReason::Fault {
+                at: 1,
+                error: "overflow combining 9223372036854775807 with 1".into()
+            }
+        );
+    }
+
+    #[test]
+    fn parses_and_formats_instructions() {
+        let set = InstructionSet::default();
+        for src in ["acc -5", "jmp +0", "add a +3", "jnz b -2", "set c d"] {
+            assert_eq!(set.format(&set.parse_instruction(src).unwrap()), src);
+        }
+        assert_eq!(
+            set.format(&set.parse_instruction("acc 5").unwrap()),
+            "acc +5"
+        );
+        for (src, error) in [
+            ("hop +1", "Unrecognized instruction: hop"),
+            ("acc", "acc takes 1 operand(s) but was given 0"),
+            ("add +1 +1", "add expects a register but was given +1"),
+            ("acc +-1", "+-1 is neither a number nor a register"),
+            ("acc x", "x is neither a number nor a register"),
+        ] {
+            assert_eq!(set.parse_instruction(src).unwrap_err(), error);
+        }
+        assert!(InstructionSet::new(&[]).is_err());
+        assert!(InstructionSet::new(&["acc", "a", "a"]).is_err());
+    }
+}
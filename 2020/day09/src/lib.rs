@@ -1,20 +1,33 @@
-use common::load_vec;
+use common::parse_vec;
+use common::solution::Solution;
 use std::collections::BTreeSet;
-use std::iter::FromIterator;
 
 const PREAMBLE_SIZE: usize = 25;
 
-fn main() {
-    let input: Vec<usize> = load_vec("input/day9.txt");
-    let bad_elem = part1(&input);
-    part2(&input, bad_elem)
+pub struct Day9;
+
+impl Solution for Day9 {
+    type Input = Vec<usize>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(src: &str) -> Result<Vec<usize>, String> {
+        parse_vec(src)
+    }
+
+    fn part1(input: &Vec<usize>) -> usize {
+        find_bad_elem(input)
+    }
+
+    fn part2(input: &Vec<usize>) -> usize {
+        part2(input, find_bad_elem(input))
+    }
 }
 
-fn part1(input: &[usize]) -> usize {
-    let mut set = BTreeSet::from_iter(input[..PREAMBLE_SIZE].iter().map(|&i| i));
+fn find_bad_elem(input: &[usize]) -> usize {
+    let mut set: BTreeSet<usize> = input[..PREAMBLE_SIZE].iter().copied().collect();
     for (idx, elem) in input[PREAMBLE_SIZE..].iter().enumerate() {
-        if let None = find_pair_summing_to(*elem, &set) {
-            println!("Part 1: {}", elem);
+        if find_pair_summing_to(*elem, &set).is_none() {
             return *elem;
         }
         set.remove(&input[idx]);
@@ -23,18 +36,14 @@ fn part1(input: &[usize]) -> usize {
     panic!("Part 1: No solution found");
 }
 
-fn part2(input: &[usize], bad_elem: usize) {
+fn part2(input: &[usize], bad_elem: usize) -> usize {
     // elements can only add to bad_elem if they are less than bad_elem
     // so split the input into subranges containing only elements < bad_elem
-    let max_ranges = input.split(|i| i >= &bad_elem).filter(|i| i.len() > 0);
+    let max_ranges = input.split(|i| i >= &bad_elem).filter(|i| !i.is_empty());
     for range in max_ranges {
         if let Some((start, end)) = find_run_adding_to(range, bad_elem) {
             let run = &range[start..=end];
-            println!(
-                "Part 2: {}",
-                run.iter().max().unwrap() + run.iter().min().unwrap()
-            );
-            return;
+            return run.iter().max().unwrap() + run.iter().min().unwrap();
         }
     }
 
@@ -42,7 +51,7 @@ fn part2(input: &[usize], bad_elem: usize) {
 }
 
 fn find_run_adding_to(src: &[usize], target: usize) -> Option<(usize, usize)> {
-    if src.len() < 1 {
+    if src.is_empty() {
         return None;
     }
     let mut sum = src[0];
@@ -0,0 +1,6 @@
+use aoc2020_day09::Day9;
+use common::solution::run_main;
+
+fn main() {
+    run_main::<Day9>(2020, 9);
+}
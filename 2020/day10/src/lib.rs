@@ -1,18 +1,36 @@
-use common::load_vec;
+use common::parse_vec;
+use common::solution::Solution;
 use std::collections::{BTreeSet, HashMap};
 use std::iter::once;
 
-fn main() {
-    let mut input: Vec<isize> = load_vec("input/day10.txt");
-    input.sort();
-    let max_joltage = input.iter().max().unwrap();
-    let device_joltage = max_joltage + 3;
-    part1(&input, device_joltage);
+pub struct Day10;
+
+impl Solution for Day10 {
+    type Input = Vec<isize>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(src: &str) -> Result<Vec<isize>, String> {
+        let mut input: Vec<isize> = parse_vec(src)?;
+        input.sort_unstable();
+        Ok(input)
+    }
+
+    fn part1(input: &Vec<isize>) -> usize {
+        part1(input, device_joltage(input))
+    }
 
-    part2(&input, device_joltage);
+    fn part2(input: &Vec<isize>) -> usize {
+        part2(input, device_joltage(input))
+    }
 }
 
-fn part1(input: &[isize], device_joltage: isize) {
+fn device_joltage(input: &[isize]) -> isize {
+    let max_joltage = input.iter().max().unwrap();
+    max_joltage + 3
+}
+
+fn part1(input: &[isize], device_joltage: isize) -> usize {
     let mut diff = JoltageDiffs::default();
     for (low, high) in once(&0)
         .chain(input.iter())
@@ -27,10 +45,10 @@ fn part1(input: &[isize], device_joltage: isize) {
         }
     }
 
-    println!("Part 1: {}", diff.ones * diff.threes);
+    diff.ones * diff.threes
 }
 
-fn part2(input: &[isize], device_joltage: isize) {
+fn part2(input: &[isize], device_joltage: isize) -> usize {
     let possible_start_joltages = input
         .iter()
         .enumerate()
@@ -38,32 +56,30 @@ fn part2(input: &[isize], device_joltage: isize) {
         .map(|(i, _)| i);
 
     let possible_next_joltages: Vec<_> = (0..input.len())
-        .map(|idx| get_possible_next_joltage_indices(idx, &input))
+        .map(|idx| get_possible_next_joltage_indices(idx, input))
         .collect();
 
     let mut counts: Vec<HashMap<usize, usize>> = (0..input.len()).map(|_| HashMap::new()).collect();
 
-    let total: usize = possible_start_joltages
+    possible_start_joltages
         .map(|joltage_idx| {
             total_combos(
-                &input,
+                input,
                 joltage_idx,
                 device_joltage,
                 &mut counts,
                 &possible_next_joltages,
             )
         })
-        .sum();
-
-    println!("Part 2: {}", total)
+        .sum()
 }
 
 fn total_combos(
     input: &[isize],
     idx: usize,
     device_joltage: isize,
-    counts: &mut Vec<HashMap<usize, usize>>,
-    possible_next_joltages: &Vec<BTreeSet<usize>>,
+    counts: &mut [HashMap<usize, usize>],
+    possible_next_joltages: &[BTreeSet<usize>],
 ) -> usize {
     if idx == input.len() - 1 {
         return 1;
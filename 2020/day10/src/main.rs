@@ -0,0 +1,6 @@
+use aoc2020_day10::Day10;
+use common::solution::run_main;
+
+fn main() {
+    run_main::<Day10>(2020, 10);
+}
@@ -1,4 +1,5 @@
-use common::load_single_object;
+use common::parse_single_object;
+use common::solution::Solution;
 use std::mem::swap;
 use std::str::FromStr;
 
@@ -13,34 +14,64 @@ const SLOPES: [(isize, isize); 8] = [
     (1, 1),
 ];
 
-fn main() {
-    let input: SeatingMap = load_single_object("input/day11.txt");
-    part1(&input);
-    part2(&input);
+pub struct Day11;
+
+impl Solution for Day11 {
+    type Input = SeatingMap;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(src: &str) -> Result<SeatingMap, String> {
+        parse_single_object(src)
+    }
+
+    fn part1(input: &SeatingMap) -> usize {
+        part1(input)
+    }
+
+    fn part2(input: &SeatingMap) -> usize {
+        part2(input)
+    }
 }
 
-fn part1(input: &SeatingMap) {
+fn part1(input: &SeatingMap) -> usize {
     let part1_config = SeatingRuleConfig {
         occupation_type_check: OccupationCheckType::Adjacent,
         max_occupied_seats: 4,
     };
     let (mut map1, mut map2) = (input.clone(), input.clone());
+    let mut generation = 0;
     while map1.step(&mut map2, part1_config) {
-        swap(&mut map1, &mut map2)
+        swap(&mut map1, &mut map2);
+        generation += 1;
+        common::event!(
+            Debug,
+            "generation",
+            generation = generation,
+            occupied = map1.count_occupied()
+        );
     }
-    println!("Part 1: {}", map1.count_occupied())
+    map1.count_occupied()
 }
 
-fn part2(input: &SeatingMap) {
+fn part2(input: &SeatingMap) -> usize {
     let part2_config = SeatingRuleConfig {
         occupation_type_check: OccupationCheckType::LineOfSight,
         max_occupied_seats: 5,
     };
     let (mut map1, mut map2) = (input.clone(), input.clone());
+    let mut generation = 0;
     while map1.step(&mut map2, part2_config) {
         swap(&mut map1, &mut map2);
+        generation += 1;
+        common::event!(
+            Debug,
+            "generation",
+            generation = generation,
+            occupied = map1.count_occupied()
+        );
     }
-    println!("Part 2: {}", map1.count_occupied())
+    map1.count_occupied()
 }
 
 #[derive(PartialEq, Debug, Copy, Clone)]
@@ -56,7 +87,7 @@ struct SeatingRuleConfig {
 }
 
 #[derive(Debug, Clone)]
-struct SeatingMap {
+pub struct SeatingMap {
     grid: Vec<Vec<SeatState>>,
     nearest_seats: Vec<Vec<Vec<(usize, usize)>>>,
     rows: isize,
@@ -78,7 +109,7 @@ impl SeatingMap {
     }
 
     fn determine_nearest_seats(
-        grid: &Vec<Vec<SeatState>>,
+        grid: &[Vec<SeatState>],
         rows: isize,
         cols: isize,
     ) -> Vec<Vec<Vec<(usize, usize)>>> {
@@ -88,7 +119,7 @@ impl SeatingMap {
             for src_col in 0..cols {
                 let mut nearest_for_seat = vec![];
                 for (drow, dcol) in SLOPES.iter() {
-                    let (mut row, mut col) = (src_row as isize + drow, src_col as isize + dcol);
+                    let (mut row, mut col) = (src_row + drow, src_col + dcol);
                     while Self::coordinate_within(rows, cols, row, col) {
                         let loc = grid[row as usize][col as usize];
                         if loc.is_seat() {
@@ -207,16 +238,16 @@ impl FromStr for SeatingMap {
             .map(|line| {
                 line.trim()
                     .chars()
-                    .filter_map(|c| SeatState::from_char(c))
+                    .filter_map(SeatState::from_char)
                     .collect()
             })
             .collect();
-        if grid.len() == 0 || grid[0].len() == 0 {
+        if grid.is_empty() || grid[0].is_empty() {
             return Err("Empty grid not supported".into());
         }
         let mut size = None;
         for elem in grid.iter() {
-            if size == None {
+            if size.is_none() {
                 size = Some(elem.len())
             }
             if size.unwrap() != elem.len() {
@@ -0,0 +1,6 @@
+use aoc2020_day11::Day11;
+use common::solution::run_main;
+
+fn main() {
+    run_main::<Day11>(2020, 11);
+}
@@ -1,24 +1,25 @@
-use common::{load_vec, modulo};
+use common::solution::Solution;
+use common::{modulo, parse_vec};
 use std::str::FromStr;
 
-fn main() {
-    let input: Vec<Movement> = load_vec("input/day12.txt");
-    part1(&input);
-    part2(&input);
-}
+pub struct Day12;
+
+impl Solution for Day12 {
+    type Input = Vec<Movement>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(src: &str) -> Result<Vec<Movement>, String> {
+        parse_vec(src)
+    }
 
-fn part1(input: &[Movement]) {
-    println!(
-        "Part 1: {}",
+    fn part1(input: &Vec<Movement>) -> usize {
         PositionWithDirection::default().navigate_and_get_position(input)
-    )
-}
+    }
 
-fn part2(input: &[Movement]) {
-    println!(
-        "Part 2: {}",
+    fn part2(input: &Vec<Movement>) -> usize {
         PositionWithWaypoint::default().navigate_and_get_position(input)
-    )
+    }
 }
 
 #[derive(Debug, Copy, Clone, Default)]
@@ -63,7 +64,7 @@ trait Positional: Default {
         self.manhattan_distance_from(&Self::default())
     }
     fn manhattan_distance_from(&self, other: &Self) -> usize {
-        (self.x() - other.x()).abs() as usize + (self.y() - other.y()).abs() as usize
+        (self.x() - other.x()).unsigned_abs() + (self.y() - other.y()).unsigned_abs()
     }
 }
 
@@ -134,9 +135,10 @@ impl Positional for PositionWithDirection {
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
 enum CardinalDirection {
     North,
+    #[default]
     East,
     South,
     West,
@@ -156,7 +158,7 @@ enum TurnDirection {
 
 impl CardinalDirection {
     fn turn(self, direction: TurnDirection, amount: usize) -> Self {
-        if amount % 90 != 0 {
+        if !amount.is_multiple_of(90) {
             panic!("Expected only right turns!");
         }
 
@@ -184,14 +186,8 @@ impl CardinalDirection {
     }
 }
 
-impl Default for CardinalDirection {
-    fn default() -> Self {
-        CardinalDirection::East
-    }
-}
-
 #[derive(Debug, Copy, Clone, PartialEq)]
-struct Movement {
+pub struct Movement {
     movement_type: MovementType,
     amount: usize,
 }
@@ -245,7 +241,7 @@ struct Waypoint {
 
 impl Waypoint {
     fn rotate(self, direction: TurnDirection, amount: usize) -> Self {
-        if amount % 90 != 0 {
+        if !amount.is_multiple_of(90) {
             panic!("Expected only right turns!");
         }
 
@@ -0,0 +1,6 @@
+use aoc2020_day12::Day12;
+use common::solution::run_main;
+
+fn main() {
+    run_main::<Day12>(2020, 12);
+}
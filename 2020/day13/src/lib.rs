@@ -1,27 +1,38 @@
-use common::{chinese_remainder, load_single_object};
+use common::solution::Solution;
+use common::{chinese_remainder, parse_single_object};
 use std::str::FromStr;
 
-fn main() {
-    let input: Input = load_single_object("input/day13.txt");
+pub struct Day13;
 
-    part1(&input);
-    part2(&input);
+impl Solution for Day13 {
+    type Input = Input;
+    type Part1 = usize;
+    type Part2 = isize;
+
+    fn parse(src: &str) -> Result<Input, String> {
+        parse_single_object(src)
+    }
+
+    fn part1(input: &Input) -> usize {
+        part1(input)
+    }
+
+    fn part2(input: &Input) -> isize {
+        part2(input)
+    }
 }
 
-fn part1(input: &Input) {
-    println!(
-        "Part 1: {}",
-        input
-            .bus_ids
-            .iter()
-            .filter_map(|&i| i.map(|id| (id, id - (input.arrival_ts % id))))
-            .min_by(|a, b| a.1.cmp(&b.1))
-            .map(|(i, j)| i * j)
-            .unwrap()
-    )
+fn part1(input: &Input) -> usize {
+    input
+        .bus_ids
+        .iter()
+        .filter_map(|&i| i.map(|id| (id, id - (input.arrival_ts % id))))
+        .min_by(|a, b| a.1.cmp(&b.1))
+        .map(|(i, j)| i * j)
+        .unwrap()
 }
 
-fn part2(input: &Input) {
+fn part2(input: &Input) -> isize {
     //(i, j) => (x + i) % j = 0
     // === x + i == 0 (mod j)
     // === x == (-i) (mod j)
@@ -32,12 +43,11 @@ fn part2(input: &Input) {
         .enumerate()
         .filter_map(|(idx, bus_id)| bus_id.map(|id| (id as isize - idx as isize, id as isize)))
         .collect();
-    let rem = chinese_remainder(&busses);
-    println!("Part 2: {}", rem);
+    chinese_remainder(&busses)
 }
 
 #[derive(Debug, Clone)]
-struct Input {
+pub struct Input {
     arrival_ts: usize,
     bus_ids: Vec<Option<usize>>,
 }
@@ -54,7 +64,7 @@ impl FromStr for Input {
 
         let arrival_ts = usize::from_str(timestamp_s).unwrap();
         let bus_ids = bus_ids_s
-            .split(",")
+            .split(',')
             .map(|bus_id| {
                 if bus_id == "x" {
                     None
@@ -0,0 +1,6 @@
+use aoc2020_day13::Day13;
+use common::solution::run_main;
+
+fn main() {
+    run_main::<Day13>(2020, 13);
+}
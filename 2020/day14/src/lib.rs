@@ -1,13 +1,28 @@
-use common::load_vec;
+use common::parse_vec;
+use common::solution::Solution;
 use lazy_static::*;
 use regex::Regex;
 use std::collections::HashMap;
 use std::str::FromStr;
 
-fn main() {
-    let input: Vec<Command> = load_vec("input/day14.txt");
-    part1(&input);
-    part2(&input);
+pub struct Day14;
+
+impl Solution for Day14 {
+    type Input = Vec<Command>;
+    type Part1 = u128;
+    type Part2 = u128;
+
+    fn parse(src: &str) -> Result<Vec<Command>, String> {
+        parse_vec(src)
+    }
+
+    fn part1(input: &Vec<Command>) -> u128 {
+        run_with_masking_type(input, MaskingType::Value)
+    }
+
+    fn part2(input: &Vec<Command>) -> u128 {
+        run_with_masking_type(input, MaskingType::Memory)
+    }
 }
 
 fn run_with_masking_type(input: &[Command], masking_type: MaskingType) -> u128 {
@@ -20,20 +35,6 @@ fn run_with_masking_type(input: &[Command], masking_type: MaskingType) -> u128 {
     machine.sum_memory()
 }
 
-fn part1(input: &[Command]) {
-    println!(
-        "Part 1: {}",
-        run_with_masking_type(input, MaskingType::Value)
-    );
-}
-
-fn part2(input: &[Command]) {
-    println!(
-        "Part 2: {}",
-        run_with_masking_type(input, MaskingType::Memory)
-    );
-}
-
 #[derive(Copy, Clone)]
 enum MaskingType {
     Value,
@@ -77,7 +78,7 @@ impl Machine {
 }
 
 #[derive(Clone, Debug)]
-enum Command {
+pub enum Command {
     SetMask(Mask),
     SetMem { location: u64, value: u64 },
 }
@@ -108,7 +109,7 @@ impl FromStr for Command {
 }
 
 #[derive(Clone, Default, Debug)]
-struct Mask {
+pub struct Mask {
     zero_mask: u64,
     one_mask: u64,
     floating_locations: Vec<usize>,
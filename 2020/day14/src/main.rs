@@ -0,0 +1,6 @@
+use aoc2020_day14::Day14;
+use common::solution::run_main;
+
+fn main() {
+    run_main::<Day14>(2020, 14);
+}
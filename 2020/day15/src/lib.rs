@@ -1,14 +1,34 @@
-use common::load_raw_text;
+use common::solution::Solution;
 use std::collections::HashMap;
 use std::str::FromStr;
 
-fn main() {
-    let input: Vec<usize> = get_input();
-    let mut game = MemoryGame::new(&input).unwrap();
-    game.run_until(2020);
-    println!("Part 1: {}", game.last_number);
-    game.run_until(30000000);
-    println!("Part 2: {}", game.last_number);
+pub struct Day15;
+
+impl Solution for Day15 {
+    type Input = Vec<usize>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(src: &str) -> Result<Vec<usize>, String> {
+        src.trim()
+            .split(',')
+            .map(|s| usize::from_str(s).map_err(|e| format!("{} ({})", e, s)))
+            .collect()
+    }
+
+    fn part1(input: &Vec<usize>) -> usize {
+        play(input, 2020)
+    }
+
+    fn part2(input: &Vec<usize>) -> usize {
+        play(input, 30000000)
+    }
+}
+
+fn play(input: &[usize], n_turns: usize) -> usize {
+    let mut game = MemoryGame::new(input).unwrap();
+    game.run_until(n_turns);
+    game.last_number
 }
 
 struct MemoryGame {
@@ -59,11 +79,3 @@ impl MemoryGame {
         }
     }
 }
-
-fn get_input() -> Vec<usize> {
-    load_raw_text("input/day15.txt")
-        .trim()
-        .split(",")
-        .map(|s| usize::from_str(s).unwrap())
-        .collect()
-}
@@ -0,0 +1,6 @@
+use aoc2020_day15::Day15;
+use common::solution::run_main;
+
+fn main() {
+    run_main::<Day15>(2020, 15);
+}
@@ -1,35 +1,42 @@
-use common::load_single_object;
+use common::parse_single_object;
+use common::solution::Solution;
 use lazy_static::*;
 use regex::Regex;
 use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
 
-fn main() {
-    let input: Input = load_single_object("input/day16.txt");
-    part1(&input);
-    part2(&input);
-}
+pub struct Day16;
+
+impl Solution for Day16 {
+    type Input = Input;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(src: &str) -> Result<Input, String> {
+        parse_single_object(src)
+    }
 
-fn part1(input: &Input) {
-    println!(
-        "{}",
+    fn part1(input: &Input) -> usize {
         input
             .nearby_tickets
             .iter()
             .flat_map(|t| t.fields_not_satisfying_a_validation(&input.validations))
-            .sum::<usize>()
-    );
+            .sum()
+    }
+
+    fn part2(input: &Input) -> usize {
+        part2(input)
+    }
 }
 
-fn part2(input: &Input) {
+fn part2(input: &Input) -> usize {
     let valid_tickets: Vec<_> = input
         .nearby_tickets
         .iter()
         .filter(|ticket| {
             ticket
                 .fields_not_satisfying_a_validation(&input.validations)
-                .len()
-                == 0
+                .is_empty()
         })
         .collect();
     let field_sets = make_field_sets(&valid_tickets);
@@ -39,7 +46,16 @@ fn part2(input: &Input) {
         .collect();
     let mut has_changed = true;
     let mut resolved_names: HashMap<String, usize> = get_resolved_names(&possible_fields_for_sets);
+    let mut round = 0;
     while has_changed && any_has_multiple_possibilities(&possible_fields_for_sets) {
+        round += 1;
+        common::event!(
+            Debug,
+            "elimination round",
+            round = round,
+            resolved = resolved_names.len(),
+            fields = possible_fields_for_sets.len()
+        );
         has_changed = false;
         for (idx, poss) in possible_fields_for_sets.iter_mut().enumerate() {
             for (name, matched_idx) in resolved_names.iter() {
@@ -55,16 +71,15 @@ fn part2(input: &Input) {
         panic!("Failed to narrow possibilities down sufficiently");
     }
 
-    let departure_prod: usize = resolved_names
+    resolved_names
         .iter()
         .filter(|(name, _)| name.starts_with("departure"))
         .map(|(_, idx)| input.your_ticket.fields[*idx])
-        .product();
-    println!("Part 2: {}", departure_prod);
+        .product()
 }
 
 fn get_resolved_names(
-    possible_fields_for_sets: &Vec<HashMap<&str, &ValidationField>>,
+    possible_fields_for_sets: &[HashMap<&str, &ValidationField>],
 ) -> HashMap<String, usize> {
     possible_fields_for_sets
         .iter()
@@ -74,7 +89,7 @@ fn get_resolved_names(
         .collect()
 }
 
-fn any_has_multiple_possibilities(possibilities: &Vec<HashMap<&str, &ValidationField>>) -> bool {
+fn any_has_multiple_possibilities(possibilities: &[HashMap<&str, &ValidationField>]) -> bool {
     possibilities.iter().map(|poss| poss.len()).any(|i| i > 1)
 }
 
@@ -221,7 +236,7 @@ impl Ticket {
     fn fields_not_satisfying_a_validation(&self, validations: &[ValidationField]) -> Vec<usize> {
         self.fields
             .iter()
-            .map(|f| *f)
+            .copied()
             .filter(|field| {
                 validations
                     .iter()
@@ -250,7 +265,7 @@ impl FromStr for Ticket {
 }
 
 #[derive(Debug, Clone)]
-struct Input {
+pub struct Input {
     validations: Vec<ValidationField>,
     your_ticket: Ticket,
     nearby_tickets: Vec<Ticket>,
@@ -273,7 +288,7 @@ impl FromStr for Input {
 
         let validations = validations
             .lines()
-            .map(|line| ValidationField::from_str(line))
+            .map(ValidationField::from_str)
             .collect::<Result<_, _>>()?;
         let (your_ticket_s, nearby_tickets_s) = {
             let mut parts = rest.split("nearby tickets:");
@@ -289,7 +304,7 @@ impl FromStr for Input {
         let your_ticket = Ticket::from_str(your_ticket_s)?;
         let nearby_tickets = nearby_tickets_s
             .lines()
-            .map(|line| Ticket::from_str(line))
+            .map(Ticket::from_str)
             .collect::<Result<_, _>>()?;
 
         Ok(Input {
@@ -0,0 +1,6 @@
+use aoc2020_day16::Day16;
+use common::solution::run_main;
+
+fn main() {
+    run_main::<Day16>(2020, 16);
+}
@@ -1,46 +1,58 @@
 mod neighbors;
-use common::load_single_object;
+use common::parse_single_object;
+use common::solution::Solution;
 use neighbors::{NEIGHBOURS_3D, NEIGHBOURS_4D};
-use num_cpus;
+use rayon::prelude::*;
 use std::cmp::{max, min};
 use std::collections::HashSet;
 use std::str::FromStr;
-use std::sync::mpsc::channel;
-use std::sync::Arc;
-use threadpool::ThreadPool;
 
-fn main() {
-    let thread_count = max(4, num_cpus::get() - 1);
-    let threadpool = ThreadPool::new(thread_count);
+pub struct Day17;
 
-    println!("Part 1: {}", run::<Coord3D>(&threadpool));
-    println!("Part 2: {}", run::<Coord4D>(&threadpool));
+impl Solution for Day17 {
+    type Input = (GameOfLife<Coord3D>, GameOfLife<Coord4D>);
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(src: &str) -> Result<Self::Input, String> {
+        Ok((parse_single_object(src)?, parse_single_object(src)?))
+    }
+
+    fn part1((input, _): &Self::Input) -> usize {
+        run(input.clone())
+    }
+
+    fn part2((_, input): &Self::Input) -> usize {
+        run(input.clone())
+    }
 }
 
-fn run<T>(pool: &ThreadPool) -> usize
+// the steps fan out over rayon's global pool, so when run from the aoc runner
+// they share its threads with whatever other days are in flight
+fn run<T>(mut input: GameOfLife<T>) -> usize
 where
-    T: Coord + Eq + std::hash::Hash + Send + Sync + 'static,
+    T: Coord + Eq + std::hash::Hash + Send + Sync,
 {
-    let mut input: GameOfLife<T> = load_single_object("input/day17.txt");
-    for _ in 0..6 {
-        input = input.step(&pool);
+    for generation in 1..=6 {
+        input = input.step();
+        common::event!(
+            Debug,
+            "generation",
+            generation = generation,
+            active = input.active_cubes.len()
+        );
     }
 
     input.active_cubes.len()
 }
 
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
 enum CubeState {
     Active,
+    #[default]
     Inactive,
 }
 
-impl Default for CubeState {
-    fn default() -> Self {
-        Self::Inactive
-    }
-}
-
 impl CubeState {
     fn from_char(c: char) -> Self {
         match c {
@@ -51,19 +63,19 @@ impl CubeState {
     }
 }
 
-trait Coord
+pub trait Coord
 where
     Self: Sized + Copy,
 {
     fn shift_all_by(&self, val: isize) -> Self;
 
-    fn get_neighbours<'a>(&'a self) -> &'a [Self];
+    fn get_neighbours(&self) -> &[Self];
 
     fn add(&self, other: &Self) -> Self;
 
     fn for_each_in_limits<F>(lower_limits: &Self, upper_limits: &Self, f: F)
     where
-        F: FnMut(Self) -> ();
+        F: FnMut(Self);
 
     fn take_each_max(&self, other: &Self) -> Self;
 
@@ -79,8 +91,8 @@ where
     fn from_pair(x: isize, y: isize) -> Self;
 }
 
-type Coord3D = (isize, isize, isize);
-type Coord4D = (isize, isize, isize, isize);
+pub type Coord3D = (isize, isize, isize);
+pub type Coord4D = (isize, isize, isize, isize);
 
 impl Coord for Coord3D {
     fn shift_all_by(&self, val: isize) -> Self {
@@ -100,7 +112,7 @@ impl Coord for Coord3D {
 
     fn for_each_in_limits<F>(lower_limits: &Self, upper_limits: &Self, mut f: F)
     where
-        F: FnMut(Self) -> (),
+        F: FnMut(Self),
     {
         let &(x_max, y_max, z_max) = upper_limits;
         let &(x_min, y_min, z_min) = lower_limits;
@@ -150,7 +162,7 @@ impl Coord for Coord4D {
 
     fn for_each_in_limits<F>(lower_limits: &Self, upper_limits: &Self, mut f: F)
     where
-        F: FnMut(Self) -> (),
+        F: FnMut(Self),
     {
         let &(x_max, y_max, z_max, w_max) = upper_limits;
         let &(x_min, y_min, z_min, w_min) = lower_limits;
@@ -184,8 +196,8 @@ impl Coord for Coord4D {
     }
 }
 
-#[derive(Debug)]
-struct GameOfLife<T>
+#[derive(Debug, Clone)]
+pub struct GameOfLife<T>
 where
     T: Coord,
 {
@@ -196,7 +208,7 @@ where
 
 impl<T> GameOfLife<T>
 where
-    T: Coord + Eq + std::hash::Hash + 'static + Send + Sync,
+    T: Coord + Eq + std::hash::Hash + Send + Sync,
 {
     fn count_active_neighbours(active_cubes: &HashSet<T>, coord: T) -> usize {
         coord
@@ -206,8 +218,8 @@ where
             .count()
     }
 
-    fn should_update_state(active_cubes: Arc<HashSet<T>>, coord: T) -> bool {
-        let active_neighbours = Self::count_active_neighbours(&active_cubes, coord);
+    fn should_update_state(active_cubes: &HashSet<T>, coord: T) -> bool {
+        let active_neighbours = Self::count_active_neighbours(active_cubes, coord);
         if active_cubes.contains(&coord) {
             active_neighbours == 2 || active_neighbours == 3
         } else {
@@ -215,29 +227,21 @@ where
         }
     }
 
-    fn step(mut self, pool: &ThreadPool) -> Self {
+    fn step(mut self) -> Self {
         let (lower, upper) = self.get_limits();
 
         // add to the limits to consider all values whose state may change
         let upper = upper.shift_all_by(2);
         let lower = lower.shift_all_by(-2);
-        let active_cubes = Arc::new(self.active_cubes);
-        let rx = {
-            let (tx, rx) = channel();
-            T::for_each_in_limits(&lower, &upper, |coord| {
-                let active_cubes = Arc::clone(&active_cubes);
-                let tx = tx.clone();
-                pool.execute(move || {
-                    if Self::should_update_state(active_cubes, coord) {
-                        tx.send(coord).unwrap();
-                    }
-                })
-            });
-            rx
-        };
+        let mut candidates = vec![];
+        T::for_each_in_limits(&lower, &upper, |coord| candidates.push(coord));
+        let active_cubes = &self.active_cubes;
 
         GameOfLife {
-            active_cubes: rx.iter().collect(),
+            active_cubes: candidates
+                .into_par_iter()
+                .filter(|&coord| Self::should_update_state(active_cubes, coord))
+                .collect(),
             lower_limits: self.lower_limits,
             upper_limits: self.upper_limits,
         }
@@ -0,0 +1,6 @@
+use aoc2020_day17::Day17;
+use common::solution::run_main;
+
+fn main() {
+    run_main::<Day17>(2020, 17);
+}
@@ -1,17 +1,26 @@
-use common::{load_vec, take_first_number};
+use common::solution::Solution;
+use common::{parse_vec, take_first_number};
 use std::iter::Peekable;
 use std::str::Chars;
 
-fn main() {
-    let input: Vec<String> = load_vec("input/day18.txt");
-    println!(
-        "Part 1: {}",
-        evaluate_and_sum(&input, MathType::Simple).unwrap()
-    );
-    println!(
-        "Part 2: {}",
-        evaluate_and_sum(&input, MathType::Advanced).unwrap()
-    );
+pub struct Day18;
+
+impl Solution for Day18 {
+    type Input = Vec<String>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(src: &str) -> Result<Vec<String>, String> {
+        parse_vec(src)
+    }
+
+    fn part1(input: &Vec<String>) -> usize {
+        evaluate_and_sum(input, MathType::Simple).unwrap()
+    }
+
+    fn part2(input: &Vec<String>) -> usize {
+        evaluate_and_sum(input, MathType::Advanced).unwrap()
+    }
 }
 
 fn evaluate_and_sum(input: &[String], math_type: MathType) -> Result<usize, String> {
@@ -0,0 +1,6 @@
+use aoc2020_day18::Day18;
+use common::solution::run_main;
+
+fn main() {
+    run_main::<Day18>(2020, 18);
+}
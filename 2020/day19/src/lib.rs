@@ -1,23 +1,32 @@
-use common::load_single_object;
+use common::parse_single_object;
+use common::solution::Solution;
 use std::str::FromStr;
 
-fn main() {
-    let input: Input = load_single_object("input/day19.txt");
-    part1(&input);
-    part2(&input);
-}
+pub struct Day19;
 
-fn part1(input: &Input) {
-    let n_valid_msgs = input
-        .strings
-        .iter()
-        .filter(|s| input.rules[0].rule.matches(s, &input.rules))
-        .count();
+impl Solution for Day19 {
+    type Input = Input;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(src: &str) -> Result<Input, String> {
+        parse_single_object(src)
+    }
 
-    println!("Part 1: {}", n_valid_msgs);
+    fn part1(input: &Input) -> usize {
+        input
+            .strings
+            .iter()
+            .filter(|s| input.rules[0].rule.matches(s, &input.rules))
+            .count()
+    }
+
+    fn part2(input: &Input) -> usize {
+        part2(input)
+    }
 }
 
-fn part2(input: &Input) {
+fn part2(input: &Input) -> usize {
     let mut rules = input.rules.clone();
     rules[8].rule = Rule::Any(vec![
         Rule::Seq(vec![Rule::Ref(42)]),
@@ -27,13 +36,11 @@ fn part2(input: &Input) {
         Rule::Seq(vec![Rule::Ref(42), Rule::Ref(31)]),
         Rule::Seq(vec![Rule::Ref(42), Rule::Ref(11), Rule::Ref(31)]),
     ]);
-    let n_valid_msgs = input
+    input
         .strings
         .iter()
         .filter(|s| Rule::Part2Rule0.matches(s, &rules))
-        .count();
-
-    println!("Part 2: {}", n_valid_msgs)
+        .count()
 }
 
 #[derive(Debug, Clone)]
@@ -46,7 +53,7 @@ enum Rule {
 }
 
 #[derive(Debug, Clone)]
-struct Input {
+pub struct Input {
     rules: Vec<RuleEntry>,
     strings: Vec<String>,
 }
@@ -201,11 +208,11 @@ impl FromStr for RuleEntry {
             (usize::from_str(part1).map_err(|e| format!("{}", e))?, part2)
         };
 
-        if rest.starts_with('"') {
-            if rest.ends_with('"') && rest.len() == 3 {
+        if let Some(quoted) = rest.strip_prefix('"') {
+            if quoted.ends_with('"') && quoted.len() == 2 {
                 Ok(RuleEntry {
                     id,
-                    rule: Rule::Single(*&rest[1..].chars().next().unwrap()),
+                    rule: Rule::Single(quoted.chars().next().unwrap()),
                 })
             } else {
                 Err(format!("Malformed rule: {}", rest))
@@ -255,9 +262,9 @@ impl FromStr for Input {
 
         let mut rules = rules
             .lines()
-            .map(|line| RuleEntry::from_str(line))
+            .map(RuleEntry::from_str)
             .collect::<Result<Vec<_>, _>>()?;
-        rules.sort_by(|a, b| a.id.cmp(&b.id));
+        rules.sort_by_key(|a| a.id);
 
         if rules
             .iter()
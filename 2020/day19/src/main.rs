@@ -0,0 +1,6 @@
+use aoc2020_day19::Day19;
+use common::solution::run_main;
+
+fn main() {
+    run_main::<Day19>(2020, 19);
+}
@@ -1,21 +1,39 @@
-use common::load_groups;
+use common::parse_groups;
+use common::solution::{Solution, Unsolved};
 use std::cmp::min;
 use std::collections::HashMap;
 use std::str::FromStr;
 
-fn main() {
-    let input: Vec<Image> = load_groups("input/day20.txt");
-    part1(&input);
-    part2(&input);
+pub struct Day20;
+
+impl Solution for Day20 {
+    type Input = Vec<Image>;
+    type Part1 = usize;
+    type Part2 = Unsolved;
+
+    fn parse(src: &str) -> Result<Vec<Image>, String> {
+        parse_groups(src)
+    }
+
+    fn part1(input: &Vec<Image>) -> usize {
+        part1(input)
+    }
+
+    fn part2(_input: &Vec<Image>) -> Unsolved {
+        /*
+         * No thanks
+         */
+        Unsolved
+    }
 }
 
-fn part1(input: &[Image]) {
+fn part1(input: &[Image]) -> usize {
     let mut all_possible_sides = HashMap::new();
     for ib in input.iter() {
         add_all(&mut all_possible_sides, &ib.borders.possible_sides(), ib.id)
     }
 
-    let corners_id_product: usize = input
+    input
         .iter()
         .filter(|ib| {
             let possible_sides = ib.borders.possible_sides();
@@ -26,15 +44,7 @@ fn part1(input: &[Image]) {
                 <= 2
         })
         .map(|corn| corn.id)
-        .product();
-
-    println!("Part 1: {}", corners_id_product);
-}
-
-fn part2(input: &[Image]) {
-    /*
-     * No thanks
-     */
+        .product()
 }
 
 fn add_all<T, U>(target: &mut HashMap<T, Vec<U>>, sides: &[T], id: U)
@@ -61,9 +71,10 @@ struct ImageBorders {
 }
 
 #[derive(Debug, Clone)]
-struct Image {
+pub struct Image {
     id: usize,
     borders: ImageBorders,
+    #[allow(dead_code)] // only needed for part 2
     contents: Vec<String>,
 }
 
@@ -0,0 +1,6 @@
+use aoc2020_day20::Day20;
+use common::solution::run_main;
+
+fn main() {
+    run_main::<Day20>(2020, 20);
+}
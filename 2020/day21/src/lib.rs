@@ -1,59 +1,77 @@
-use common::load_vec;
+use common::parse_vec;
+use common::solution::Solution;
 use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
 
-fn main() {
-    let input: Vec<Food> = load_vec("input/day21.txt");
-    let map = ingredient_allergen_map(&input);
-    let assigned_ingredients = map.values().collect::<HashSet<_>>();
-    let non_allergenic: Vec<_> = all_ingredients(&input)
-        .into_iter()
-        .filter(|i| !assigned_ingredients.contains(*i))
-        .collect();
+pub struct Day21;
 
-    let occurrences: usize = input
-        .iter()
-        .map(|f| {
-            non_allergenic
-                .iter()
-                .filter(|n| f.ingredients.contains(&n[..]))
-                .count()
-        })
-        .sum();
+impl Solution for Day21 {
+    type Input = Vec<Food>;
+    type Part1 = usize;
+    type Part2 = String;
+
+    fn parse(src: &str) -> Result<Vec<Food>, String> {
+        parse_vec(src)
+    }
 
-    println!("Part 1: {}", occurrences);
+    fn part1(input: &Vec<Food>) -> usize {
+        let map = ingredient_allergen_map(input);
+        let assigned_ingredients = map.values().collect::<HashSet<_>>();
+        let non_allergenic: Vec<_> = all_ingredients(input)
+            .into_iter()
+            .filter(|i| !assigned_ingredients.contains(*i))
+            .collect();
 
-    println!(
-        "Part 2: {}",
-        create_canonical_dangerous_ingredient_list(&map)
-    );
+        input
+            .iter()
+            .map(|f| {
+                non_allergenic
+                    .iter()
+                    .filter(|n| f.ingredients.contains(&n[..]))
+                    .count()
+            })
+            .sum()
+    }
+
+    fn part2(input: &Vec<Food>) -> String {
+        create_canonical_dangerous_ingredient_list(&ingredient_allergen_map(input))
+    }
 }
 
 fn create_canonical_dangerous_ingredient_list(src: &HashMap<String, String>) -> String {
     let mut elements: Vec<_> = src.iter().collect();
-    elements.sort_by(|(k1, _), (k2, _)| k1.cmp(&k2));
+    elements.sort_by_key(|(k1, _)| *k1);
     elements
         .iter()
         .fold(None, |acc, (_, v)| match acc {
             None => Some((*v).clone()),
-            Some(a) => Some(String::from(a + "," + v)),
+            Some(a) => Some(a + "," + v),
         })
         .unwrap()
 }
 
 #[derive(Debug)]
-struct Food {
+pub struct Food {
     ingredients: HashSet<String>,
     allergens: HashSet<String>,
 }
 
 fn ingredient_allergen_map(food: &[Food]) -> HashMap<String, String> {
     let mut made_progress = true;
-    let mut allergen_ingred_map = allergen_ingred_map(&food);
+    let mut allergen_ingred_map = allergen_ingred_map(food);
     let allergens_count = allergen_ingred_map.len();
     let mut assigned_allergens: HashMap<String, String> = HashMap::new();
     let mut assigned_ingredients: HashSet<String> = HashSet::new();
+    let mut round = 0;
     while made_progress && assigned_allergens.len() < allergens_count {
+        round += 1;
+        common::event!(
+            Debug,
+            "elimination round",
+            round = round,
+            assigned = assigned_allergens.len(),
+            allergens = allergens_count
+        );
         made_progress = false;
         allergen_ingred_map = allergen_ingred_map
             .iter()
@@ -63,7 +81,7 @@ fn ingredient_allergen_map(food: &[Food]) -> HashMap<String, String> {
                     ingredients
                         .iter()
                         .filter(|ingred| !assigned_ingredients.contains(*ingred))
-                        .map(|ingred| String::from(ingred))
+                        .map(String::from)
                         .collect(),
                 )
             })
@@ -97,9 +115,7 @@ fn allergen_ingred_map(food: &[Food]) -> HashMap<String, HashSet<String>> {
                 let set = map.remove(&allergen[..]).unwrap();
                 map.insert(
                     allergen.clone(),
-                    set.intersection(&food_item.ingredients)
-                        .map(|c| c.clone())
-                        .collect(),
+                    set.intersection(&food_item.ingredients).cloned().collect(),
                 );
             }
         }
@@ -107,10 +123,10 @@ fn allergen_ingred_map(food: &[Food]) -> HashMap<String, HashSet<String>> {
     map
 }
 
-fn all_ingredients<'a>(food: &'a [Food]) -> HashSet<&'a String> {
+fn all_ingredients(food: &[Food]) -> HashSet<&String> {
     food.iter().fold(HashSet::new(), |acc, i| {
         acc.union(&i.ingredients.iter().collect())
-            .map(|i| *i)
+            .copied()
             .collect()
     })
 }
@@ -133,12 +149,12 @@ impl FromStr for Food {
         Ok(Food {
             ingredients: ingredients_s
                 .split(" ")
-                .filter(|s| s.trim().len() != 0)
+                .filter(|s| !s.trim().is_empty())
                 .map(|s| String::from(s.trim()))
                 .collect(),
             allergens: allergens_s
                 .split(" ")
-                .filter(|s| s.trim().len() != 0)
+                .filter(|s| !s.trim().is_empty())
                 .map(|s| String::from(s.trim()))
                 .collect(),
         })
@@ -0,0 +1,6 @@
+use aoc2020_day21::Day21;
+use common::solution::run_main;
+
+fn main() {
+    run_main::<Day21>(2020, 21);
+}
@@ -1,29 +1,36 @@
-use common::load_groups;
+use common::parse_groups;
+use common::solution::Solution;
 use std::cmp::max;
 use std::collections::{HashSet, VecDeque};
 use std::str::FromStr;
 
-fn main() {
-    let (player1, player2) = {
-        let input: Vec<Player> = load_groups("input/day22.txt");
+pub struct Day22;
+
+impl Solution for Day22 {
+    type Input = (Player, Player);
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(src: &str) -> Result<(Player, Player), String> {
+        let input: Vec<Player> = parse_groups(src)?;
         let mut iter = input.into_iter();
-        (iter.next().unwrap(), iter.next().unwrap())
-    };
+        match (iter.next(), iter.next()) {
+            (Some(player1), Some(player2)) => Ok((player1, player2)),
+            _ => Err("Expected two players".into()),
+        }
+    }
 
-    let winning_score = play_standard(&mut player1.clone(), &mut player2.clone());
-    println!("Part 1: {}", winning_score);
+    fn part1((player1, player2): &(Player, Player)) -> usize {
+        play_standard(&mut player1.clone(), &mut player2.clone())
+    }
 
-    let winning_score = play_recursive(HashSet::new(), &mut player1.clone(), &mut player2.clone());
-    println!("Part 2: {}", winning_score.score);
+    fn part2((player1, player2): &(Player, Player)) -> usize {
+        play_game(&mut player1.clone(), &mut player2.clone()).score
+    }
 }
 
 fn play_standard(player1: &mut Player, player2: &mut Player) -> usize {
-    loop {
-        let p1card = if let Some(c) = player1.draw() {
-            c
-        } else {
-            break;
-        };
+    while let Some(p1card) = player1.draw() {
         let p2card = if let Some(d) = player2.draw() {
             d
         } else {
@@ -51,17 +58,35 @@ struct RoundResult {
     score: usize,
 }
 
+// a fresh game of recursive combat, with no rounds played yet
+fn play_game(player1: &mut Player, player2: &mut Player) -> RoundResult {
+    let _span = common::span!(
+        Debug,
+        "game",
+        player1_cards = player1.deck.len(),
+        player2_cards = player2.deck.len()
+    );
+    let result = play_recursive(HashSet::new(), player1, player2);
+    common::event!(
+        Debug,
+        "game over",
+        winner = format!("{:?}", result.winner),
+        score = result.score
+    );
+    result
+}
+
 fn play_recursive<'a>(
     mut prev_rounds: HashSet<(Vec<u8>, Vec<u8>)>,
     mut player1: &'a mut Player,
     mut player2: &'a mut Player,
 ) -> RoundResult {
-    if player1.deck.len() == 0 {
+    if player1.deck.is_empty() {
         return RoundResult {
             winner: PlayerId::Player2,
             score: player2.score(),
         };
-    } else if player2.deck.len() == 0 {
+    } else if player2.deck.is_empty() {
         return RoundResult {
             winner: PlayerId::Player1,
             score: player1.score(),
@@ -69,8 +94,8 @@ fn play_recursive<'a>(
     }
 
     let key = (
-        player1.deck.iter().map(|i| *i).collect(),
-        player2.deck.iter().map(|i| *i).collect(),
+        player1.deck.iter().copied().collect(),
+        player2.deck.iter().copied().collect(),
     );
     if prev_rounds.contains(&key) {
         return RoundResult {
@@ -86,8 +111,7 @@ fn play_recursive<'a>(
 
     let winner = if player1.deck.len() >= card1 && player2.deck.len() >= card2 {
         // can play a recursive subgame
-        let subgame_result = play_recursive(
-            HashSet::new(),
+        let subgame_result = play_game(
             &mut player1.copy_n(card1).unwrap(),
             &mut player2.copy_n(card2).unwrap(),
         );
@@ -115,7 +139,7 @@ fn play_recursive<'a>(
 }
 
 #[derive(Debug, Clone)]
-struct Player {
+pub struct Player {
     id: PlayerId,
     deck: VecDeque<u8>,
 }
@@ -149,7 +173,7 @@ impl Player {
 
         Some(Player {
             id: self.id,
-            deck: self.deck.iter().take(n).map(|i| *i).collect(),
+            deck: self.deck.iter().take(n).copied().collect(),
         })
     }
 }
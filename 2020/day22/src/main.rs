@@ -0,0 +1,6 @@
+use aoc2020_day22::Day22;
+use common::solution::run_main;
+
+fn main() {
+    run_main::<Day22>(2020, 22);
+}
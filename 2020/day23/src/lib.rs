@@ -1,29 +1,40 @@
+use common::solution::Solution;
 use std::collections::VecDeque;
-use std::str::FromStr;
-
-fn get_input() -> VecDeque<usize> {
-    // VecDeque::from(vec![1, 3, 5, 4, 6, 8, 7, 2, 9])
-    "135468729"
-        .chars()
-        .map(|c| usize::from_str(&format!("{}", c)).unwrap())
-        .collect()
-}
 
-fn main() {
-    part1();
-    part2();
+pub struct Day23;
+
+impl Solution for Day23 {
+    type Input = VecDeque<usize>;
+    type Part1 = String;
+    type Part2 = usize;
+
+    fn parse(src: &str) -> Result<VecDeque<usize>, String> {
+        src.trim()
+            .chars()
+            .map(|c| {
+                c.to_digit(10)
+                    .map(|d| d as usize)
+                    .ok_or(format!("Expected a digit but got '{}'", c))
+            })
+            .collect()
+    }
+
+    fn part1(input: &VecDeque<usize>) -> String {
+        part1(input.clone())
+    }
+
+    fn part2(input: &VecDeque<usize>) -> usize {
+        part2(input.clone())
+    }
 }
 
-fn part1() {
-    let mut input = get_input();
+fn part1(mut input: VecDeque<usize>) -> String {
     input.rotate_left(1);
     play_until(100, &mut input);
-    let result = input.iter().fold(String::new(), |mut acc, i| {
+    input.iter().fold(String::new(), |mut acc, i| {
         acc.push_str(&format!("{}", i)[..]);
         acc
-    });
-
-    println!("Part 1: {}", result);
+    })
 }
 
 fn play_until(end: usize, cups: &mut VecDeque<usize>) {
@@ -36,8 +47,8 @@ fn play_until(end: usize, cups: &mut VecDeque<usize>) {
     cups.pop_front();
 }
 
-fn part2() {
-    let mut next_cup = transform_input(get_input());
+fn part2(input: VecDeque<usize>) -> usize {
+    let mut next_cup = transform_input(input);
 
     let mut current_cup = next_cup[0];
     for _ in 0..10_000_000 {
@@ -81,8 +92,7 @@ fn part2() {
         current_cup = next_cup[current_cup]
     }
 
-    let result = next_cup[1] * next_cup[next_cup[1]];
-    println!("Part 2: {}", result);
+    next_cup[1] * next_cup[next_cup[1]]
 }
 
 fn play(cups: &mut VecDeque<usize>) {
@@ -107,8 +117,8 @@ fn play(cups: &mut VecDeque<usize>) {
     cups.rotate_right(loc);
 }
 
-fn transform_input(input: VecDeque<usize>) -> [usize; 1_000_001] {
-    let mut result = [0; 1_000_001];
+fn transform_input(input: VecDeque<usize>) -> Vec<usize> {
+    let mut result = vec![0; 1_000_001];
     result[0] = input[0]; // the current cup
     let max = input.iter().max().unwrap();
     for idx in 0..input.len() - 1 {
@@ -116,8 +126,8 @@ fn transform_input(input: VecDeque<usize>) -> [usize; 1_000_001] {
     }
     result[*input.back().unwrap()] = max + 1;
 
-    for i in max + 1..1_000_000 {
-        result[i] = i + 1;
+    for (i, next) in result.iter_mut().enumerate().take(1_000_000).skip(max + 1) {
+        *next = i + 1;
     }
 
     result[1_000_000] = *input.front().unwrap();
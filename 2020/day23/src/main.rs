@@ -0,0 +1,6 @@
+use aoc2020_day23::Day23;
+use common::solution::run_main;
+
+fn main() {
+    run_main::<Day23>(2020, 23);
+}
@@ -1,4 +1,5 @@
-use common::load_vec;
+use common::parse_vec;
+use common::solution::Solution;
 use std::cmp::{max, min};
 use std::collections::HashMap;
 use std::iter::Sum;
@@ -6,18 +7,36 @@ use std::ops::Add;
 use std::str::FromStr;
 use Direction::*;
 
-fn main() {
-    let input: Vec<TileDescription> = load_vec("input/day24.txt");
-    let mut layout = initial_layout(&input);
-    let black_tiles_count = count_with_color(&layout, TileColor::Black);
-    println!("Part 1: {}", black_tiles_count);
+pub struct Day24;
 
-    for _ in 0..100 {
-        update_layout(&mut layout);
+impl Solution for Day24 {
+    type Input = Vec<TileDescription>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(src: &str) -> Result<Vec<TileDescription>, String> {
+        parse_vec(src)
+    }
+
+    fn part1(input: &Vec<TileDescription>) -> usize {
+        let layout = initial_layout(input);
+        count_with_color(&layout, TileColor::Black)
     }
 
-    let updated_black_tiles_count = count_with_color(&layout, TileColor::Black);
-    println!("Part 2: {}", updated_black_tiles_count);
+    fn part2(input: &Vec<TileDescription>) -> usize {
+        let mut layout = initial_layout(input);
+        for generation in 1..=100 {
+            update_layout(&mut layout);
+            common::event!(
+                Debug,
+                "generation",
+                generation = generation,
+                black = count_with_color(&layout, TileColor::Black)
+            );
+        }
+
+        count_with_color(&layout, TileColor::Black)
+    }
 }
 
 fn count_with_color(layout: &HashMap<Coord, TileColor>, color: TileColor) -> usize {
@@ -89,9 +108,10 @@ fn count_neighbours_with_color(
         .count()
 }
 
-#[derive(Debug, PartialEq, Copy, Clone)]
+#[derive(Debug, PartialEq, Copy, Clone, Default)]
 enum TileColor {
     Black,
+    #[default]
     White,
 }
 
@@ -104,12 +124,6 @@ impl TileColor {
     }
 }
 
-impl Default for TileColor {
-    fn default() -> Self {
-        Self::White
-    }
-}
-
 impl Default for &TileColor {
     fn default() -> Self {
         &TileColor::White
@@ -117,7 +131,7 @@ impl Default for &TileColor {
 }
 
 #[derive(Debug, Clone)]
-struct TileDescription {
+pub struct TileDescription {
     directions: Vec<Direction>,
 }
 
@@ -202,7 +216,7 @@ impl FromStr for TileDescription {
                 None => break,
                 Some(c) if c == 'e' || c == 'w' => Direction::from_str(&c.to_string()),
                 Some(c) if c == 's' || c == 'n' => {
-                    let c2 = chars.next().ok_or(format!("Unexpected end of input"))?;
+                    let c2 = chars.next().ok_or("Unexpected end of input".to_string())?;
                     if c2 != 'e' && c2 != 'w' {
                         return Err(format!("Unexpected direction: {}{}", c, c2));
                     }
@@ -0,0 +1,6 @@
+use aoc2020_day24::Day24;
+use common::solution::run_main;
+
+fn main() {
+    run_main::<Day24>(2020, 24);
+}
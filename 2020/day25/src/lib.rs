@@ -0,0 +1,44 @@
+use common::parse_vec;
+use common::solution::{Solution, Unsolved};
+
+const DIV: usize = 20201227;
+
+pub struct Day25;
+
+impl Solution for Day25 {
+    // (card public key, door public key)
+    type Input = (usize, usize);
+    type Part1 = usize;
+    type Part2 = Unsolved;
+
+    fn parse(src: &str) -> Result<(usize, usize), String> {
+        match parse_vec(src)?[..] {
+            [card_pk, door_pk] => Ok((card_pk, door_pk)),
+            _ => Err("Expected exactly two public keys".into()),
+        }
+    }
+
+    fn part1(&(card_pk, door_pk): &(usize, usize)) -> usize {
+        let subject_number_card = 7;
+        let subject_number_door = door_pk;
+        let (mut key_card, mut key_door) = (1, 1);
+        for _ in 1..DIV {
+            if key_card == card_pk {
+                break;
+            }
+            key_card = step(key_card, subject_number_card);
+            key_door = step(key_door, subject_number_door);
+        }
+
+        key_door
+    }
+
+    fn part2(_input: &(usize, usize)) -> Unsolved {
+        // day 25 has no second part
+        Unsolved
+    }
+}
+
+fn step(i: usize, subject_number: usize) -> usize {
+    (i * subject_number) % DIV
+}
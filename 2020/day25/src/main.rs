@@ -0,0 +1,6 @@
+use aoc2020_day25::Day25;
+use common::solution::run_main;
+
+fn main() {
+    run_main::<Day25>(2020, 25);
+}
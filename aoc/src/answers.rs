@@ -0,0 +1,75 @@
+use common::answers_file;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+// known answers for a year, one per line as `<day> <part> <answer>`
+#[derive(Debug, Default)]
+pub struct Answers {
+    known: HashMap<(u8, usize), String>,
+}
+
+impl Answers {
+    // a year without a manifest simply has nothing to check against
+    pub fn load(year: u16) -> Result<Self, String> {
+        let file_name = answers_file(year);
+        match std::fs::read_to_string(&file_name) {
+            Ok(src) => Answers::from_str(&src).map_err(|e| format!("{}: {}", file_name, e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Answers::default()),
+            Err(e) => Err(format!("{}: {}", file_name, e)),
+        }
+    }
+
+    pub fn expected(&self, day: u8, part: usize) -> Option<&str> {
+        self.known.get(&(day, part)).map(|s| &s[..])
+    }
+}
+
+impl FromStr for Answers {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut known = HashMap::new();
+        for (idx, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut parts = line.splitn(3, ' ');
+            let (day, part, answer) = match (parts.next(), parts.next(), parts.next()) {
+                (Some(day), Some(part), Some(answer)) => (day, part, answer),
+                _ => return Err(format!("line {}: expected <day> <part> <answer>", idx + 1)),
+            };
+            let day = u8::from_str(day).map_err(|e| format!("line {}: {}", idx + 1, e))?;
+            let part = usize::from_str(part).map_err(|e| format!("line {}: {}", idx + 1, e))?;
+            known.insert((day, part), answer.trim().into());
+        }
+
+        Ok(Answers { known })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_answers_skipping_comments_and_blanks() {
+        let answers =
+            Answers::from_str("# 2020\n1 1 482811\n\n1 2 an answer with spaces \n").unwrap();
+        assert_eq!(answers.expected(1, 1), Some("482811"));
+        assert_eq!(answers.expected(1, 2), Some("an answer with spaces"));
+        assert_eq!(answers.expected(2, 1), None);
+    }
+
+    #[test]
+    fn reports_the_bad_line() {
+        assert_eq!(
+            Answers::from_str("1 1 5\n1 2\n").unwrap_err(),
+            "line 2: expected <day> <part> <answer>"
+        );
+        assert!(Answers::from_str("x 1 5")
+            .unwrap_err()
+            .starts_with("line 1: "));
+    }
+}
@@ -0,0 +1,395 @@
+mod answers;
+mod registry;
+mod scaffold;
+mod scheduler;
+
+use answers::Answers;
+use common::inputs::{DirectoryProvider, InputCache};
+use common::memory::{self, CountingAllocator};
+use common::trace::{self, Format, Level};
+use registry::Entry;
+use scheduler::{init_pool, run_in_order, Job, Outcome};
+use std::collections::hash_map::{self, HashMap};
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+
+const USAGE: &str = "usage:
+  aoc run <year|all> [day|all] [--jobs N] [--mem] [--offline] [--inputs-from DIR]
+          [--log-level LEVEL] [--log-json FILE|-]
+  aoc fetch <year> [day|all] [--inputs-from DIR]
+  aoc new <year> <day>
+
+Missing inputs are fetched from --inputs-from, $AOC_INPUT_DIR, or adventofcode.com
+when $AOC_SESSION holds a session cookie ($AOC_BASE_URL overrides the host).
+--mem reports heap usage for every phase, running one day at a time.
+Logging (error, warn, info, debug or trace) goes to stderr as text, or as JSON lines
+to --log-json; it needs a build with `--features trace`.";
+
+// only counts once `aoc run --mem` enables it
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Err(e) = run(&args) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
+
+struct RunArgs {
+    days: Vec<&'static Entry>,
+    jobs: usize,
+    memory: bool,
+    inputs: InputArgs,
+    log: LogArgs,
+}
+
+struct FetchArgs {
+    year: u16,
+    days: Vec<u8>,
+    inputs: InputArgs,
+}
+
+// where missing inputs come from, on top of the environment
+#[derive(Default)]
+struct InputArgs {
+    offline: bool,
+    from_dir: Option<String>,
+}
+
+#[derive(Default)]
+struct LogArgs {
+    level: Option<Level>,
+    json: Option<String>,
+}
+
+impl InputArgs {
+    fn cache(&self) -> InputCache {
+        let cache = InputCache::from_env();
+        if self.offline {
+            cache.offline()
+        } else if let Some(dir) = &self.from_dir {
+            cache.with_provider(DirectoryProvider::new(dir))
+        } else {
+            cache
+        }
+    }
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+    match args.split_first() {
+        Some((command, rest)) if command == "run" => {
+            let run_args = parse_run_args(rest).map_err(usage_error)?;
+            run_days(&run_args)
+        }
+        Some((command, rest)) if command == "fetch" => {
+            let fetch_args = parse_fetch_args(rest).map_err(usage_error)?;
+            fetch_days(&fetch_args)
+        }
+        Some((command, rest)) if command == "new" => {
+            let (year, day) = parse_new_args(rest).map_err(usage_error)?;
+            new_day(year, day)
+        }
+        Some((command, _)) => Err(usage_error(format!("Unknown command: {}", command))),
+        None => Err(usage_error("No command given".into())),
+    }
+}
+
+fn usage_error(e: String) -> String {
+    format!("{}\n{}", e, USAGE)
+}
+
+fn parse_run_args(args: &[String]) -> Result<RunArgs, String> {
+    let mut positional = vec![];
+    let mut jobs = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1);
+    let mut memory = false;
+    let mut inputs = InputArgs::default();
+    let mut log = LogArgs::default();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match &arg[..] {
+            "--jobs" | "-j" => {
+                let value = args.next().ok_or("Expected a value after --jobs")?;
+                jobs = usize::from_str(value)
+                    .ok()
+                    .filter(|&jobs| jobs > 0)
+                    .ok_or(format!("Invalid job count: {}", value))?;
+            }
+            "--mem" => memory = true,
+            "--log-level" => {
+                let value = args.next().ok_or("Expected a level after --log-level")?;
+                log.level = Some(Level::from_str(value)?);
+            }
+            "--log-json" => {
+                let value = args.next().ok_or("Expected a file after --log-json")?;
+                log.json = Some(value.clone());
+            }
+            "--offline" => inputs.offline = true,
+            "--inputs-from" => {
+                let value = args
+                    .next()
+                    .ok_or("Expected a directory after --inputs-from")?;
+                inputs.from_dir = Some(value.clone());
+            }
+            _ => positional.push(&arg[..]),
+        }
+    }
+
+    // the allocation counters are shared by the whole process, so days mustn't overlap
+    if memory {
+        jobs = 1;
+    }
+
+    let days = match positional[..] {
+        ["all"] | ["all", "all"] => registry::all().collect(),
+        [year] | [year, "all"] => year_entries(year)?.iter().collect(),
+        [year, day] => {
+            let year = parse_year(year)?;
+            let day = parse_day(day)?;
+            vec![registry::find(year, day)
+                .ok_or(format!("No solution registered for {} day {}", year, day))?]
+        }
+        [] => return Err("Expected a year or 'all'".into()),
+        _ => return Err(format!("Unexpected arguments: {}", positional.join(" "))),
+    };
+
+    Ok(RunArgs {
+        days,
+        jobs,
+        memory,
+        inputs,
+        log,
+    })
+}
+
+fn parse_fetch_args(args: &[String]) -> Result<FetchArgs, String> {
+    let mut positional = vec![];
+    let mut inputs = InputArgs::default();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match &arg[..] {
+            "--inputs-from" => {
+                let value = args
+                    .next()
+                    .ok_or("Expected a directory after --inputs-from")?;
+                inputs.from_dir = Some(value.clone());
+            }
+            _ => positional.push(&arg[..]),
+        }
+    }
+
+    // "all" means every registered day, since later days may not be unlocked yet
+    let (year, days) = match positional[..] {
+        [year] | [year, "all"] => {
+            let days = year_entries(year)?.iter().map(|entry| entry.day).collect();
+            (parse_year(year)?, days)
+        }
+        [year, day] => (parse_year(year)?, vec![parse_day(day)?]),
+        [] => return Err("Expected a year".into()),
+        _ => return Err(format!("Unexpected arguments: {}", positional.join(" "))),
+    };
+
+    Ok(FetchArgs { year, days, inputs })
+}
+
+fn parse_new_args(args: &[String]) -> Result<(u16, u8), String> {
+    match args {
+        [year, day] => Ok((parse_year(year)?, parse_day(day)?)),
+        _ => Err("Expected <year> <day>".into()),
+    }
+}
+
+fn year_entries(year: &str) -> Result<&'static [Entry], String> {
+    let year = parse_year(year)?;
+    registry::year(year).ok_or(format!("No solutions registered for {}", year))
+}
+
+fn parse_year(year: &str) -> Result<u16, String> {
+    u16::from_str(year)
+        .ok()
+        .filter(|&year| year >= 2015)
+        .ok_or(format!("Invalid year: {}", year))
+}
+
+fn parse_day(day: &str) -> Result<u8, String> {
+    u8::from_str(day)
+        .ok()
+        .filter(|day| (1..=25).contains(day))
+        .ok_or(format!("Invalid day: {}", day))
+}
+
+fn run_days(args: &RunArgs) -> Result<(), String> {
+    init_logging(&args.log)?;
+
+    let mut answers = HashMap::new();
+    for entry in args.days.iter() {
+        if let hash_map::Entry::Vacant(slot) = answers.entry(entry.year) {
+            slot.insert(Answers::load(entry.year)?);
+        }
+    }
+
+    // resolve (and if need be fetch) every input up front, one request at a time
+    let cache = args.inputs.cache();
+    let jobs = args
+        .days
+        .iter()
+        .map(|&entry| Job {
+            entry,
+            input: cache.get(entry.year, entry.day),
+        })
+        .collect();
+
+    init_pool(args.jobs)?;
+    if args.memory {
+        memory::enable()?;
+    }
+
+    let mut failures = 0;
+    let timings = run_in_order(jobs, |outcome| {
+        if !print_outcome(&outcome, &answers[&outcome.entry.year]) {
+            failures += 1;
+        }
+    });
+
+    println!(
+        "Total: {} wall, {} cpu ({:.1}x on {} jobs)",
+        format_duration(timings.wall),
+        format_duration(timings.cpu),
+        timings.cpu.as_secs_f64() / timings.wall.as_secs_f64(),
+        args.jobs
+    );
+
+    if failures > 0 {
+        Err(format!("{} day(s) failed", failures))
+    } else {
+        Ok(())
+    }
+}
+
+fn init_logging(args: &LogArgs) -> Result<(), String> {
+    let level = match (args.level, &args.json) {
+        (Some(level), _) => level,
+        (None, Some(_)) => Level::Info,
+        (None, None) => return Ok(()),
+    };
+    // unbuffered, since the sink is never dropped to flush it
+    let (format, out): (_, Box<dyn Write + Send>) = match args.json.as_deref() {
+        None => (Format::Text, Box::new(io::stderr())),
+        Some("-") => (Format::Json, Box::new(io::stderr())),
+        Some(path) => (
+            Format::Json,
+            Box::new(File::create(path).map_err(|e| format!("{}: {}", path, e))?),
+        ),
+    };
+    trace::init(level, format, out)
+}
+
+// returns false if the day failed or disagreed with a known answer
+fn print_outcome(outcome: &Outcome, answers: &Answers) -> bool {
+    let Entry { year, day, .. } = *outcome.entry;
+    match &outcome.result {
+        Ok(report) => {
+            println!(
+                "{} day {} ({})",
+                year,
+                day,
+                format_duration(report.elapsed())
+            );
+            let mut all_correct = true;
+            for (idx, (name, answer)) in report.answers().enumerate() {
+                match answers.expected(day, idx + 1) {
+                    Some(expected) if expected != answer => {
+                        all_correct = false;
+                        println!("  {}: {} (expected {})", name, answer, expected);
+                    }
+                    _ => println!("  {}: {}", name, answer),
+                }
+            }
+            for phase in report.phases.iter() {
+                if let Some(usage) = phase.memory {
+                    println!(
+                        "  {} memory: peak {}, {} allocations, {} allocated",
+                        phase.name,
+                        format_bytes(usage.peak as u64),
+                        usage.allocations,
+                        format_bytes(usage.allocated)
+                    );
+                }
+            }
+            all_correct
+        }
+        Err(e) => {
+            println!("{} day {} failed: {}", year, day, e);
+            false
+        }
+    }
+}
+
+fn fetch_days(args: &FetchArgs) -> Result<(), String> {
+    let cache = args.inputs.cache();
+    let mut failures = 0;
+    for &day in args.days.iter() {
+        match cache.get(args.year, day) {
+            Ok(_) => println!(
+                "{} day {}: {}",
+                args.year,
+                day,
+                cache.path(args.year, day).display()
+            ),
+            Err(e) => {
+                println!("{} day {} failed: {}", args.year, day, e);
+                failures += 1;
+            }
+        }
+    }
+
+    if failures > 0 {
+        Err(format!("{} input(s) could not be fetched", failures))
+    } else {
+        Ok(())
+    }
+}
+
+fn new_day(year: u16, day: u8) -> Result<(), String> {
+    for path in scaffold::new_day(Path::new("."), year, day)? {
+        println!("wrote {}", path);
+    }
+    println!(
+        "Fetch the puzzle input with `aoc fetch {year} {day}` (or put it in {}) and run it with `aoc run {year} {day}`",
+        common::input_file(year, day),
+        year = year,
+        day = day
+    );
+    Ok(())
+}
+
+fn format_duration(duration: Duration) -> String {
+    if duration.as_secs() > 0 {
+        format!("{:.2}s", duration.as_secs_f64())
+    } else {
+        format!("{:.2}ms", duration.as_secs_f64() * 1000.0)
+    }
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{}B", bytes)
+    } else {
+        format!("{:.1}{}", value, UNITS[unit])
+    }
+}
@@ -0,0 +1,57 @@
+use common::solution::{run, Report, Solution};
+
+mod y2020;
+
+// `aoc new` adds new years above the marker lines, so keep them in place
+pub const YEARS: &[(u16, &[Entry])] = &[
+    (2020, y2020::DAYS),
+    // aoc new: years
+];
+
+// rough relative cost of a day, used to start the slow days first
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Cost {
+    Light,
+    Heavy,
+}
+
+#[derive(Copy, Clone)]
+pub struct Entry {
+    pub year: u16,
+    pub day: u8,
+    pub cost: Cost,
+    pub run: fn(&str) -> Result<Report, String>,
+}
+
+pub const fn light<S: Solution>(year: u16, day: u8) -> Entry {
+    Entry {
+        year,
+        day,
+        cost: Cost::Light,
+        run: run::<S>,
+    }
+}
+
+pub const fn heavy<S: Solution>(year: u16, day: u8) -> Entry {
+    Entry {
+        year,
+        day,
+        cost: Cost::Heavy,
+        run: run::<S>,
+    }
+}
+
+pub fn all() -> impl Iterator<Item = &'static Entry> {
+    YEARS.iter().flat_map(|(_, days)| days.iter())
+}
+
+pub fn year(year: u16) -> Option<&'static [Entry]> {
+    YEARS
+        .iter()
+        .find(|(y, _)| *y == year)
+        .map(|(_, days)| *days)
+}
+
+pub fn find(year: u16, day: u8) -> Option<&'static Entry> {
+    self::year(year)?.iter().find(|entry| entry.day == day)
+}
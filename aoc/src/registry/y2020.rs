@@ -0,0 +1,30 @@
+use super::{heavy, light, Entry};
+
+pub const DAYS: &[Entry] = &[
+    light::<aoc2020_day01::Day1>(2020, 1),
+    light::<aoc2020_day02::Day2>(2020, 2),
+    light::<aoc2020_day03::Day3>(2020, 3),
+    light::<aoc2020_day04::Day4>(2020, 4),
+    light::<aoc2020_day05::Day5>(2020, 5),
+    light::<aoc2020_day06::Day6>(2020, 6),
+    light::<aoc2020_day07::Day7>(2020, 7),
+    light::<aoc2020_day08::Day8>(2020, 8),
+    light::<aoc2020_day09::Day9>(2020, 9),
+    light::<aoc2020_day10::Day10>(2020, 10),
+    light::<aoc2020_day11::Day11>(2020, 11),
+    light::<aoc2020_day12::Day12>(2020, 12),
+    light::<aoc2020_day13::Day13>(2020, 13),
+    light::<aoc2020_day14::Day14>(2020, 14),
+    heavy::<aoc2020_day15::Day15>(2020, 15),
+    light::<aoc2020_day16::Day16>(2020, 16),
+    heavy::<aoc2020_day17::Day17>(2020, 17),
+    light::<aoc2020_day18::Day18>(2020, 18),
+    light::<aoc2020_day19::Day19>(2020, 19),
+    light::<aoc2020_day20::Day20>(2020, 20),
+    light::<aoc2020_day21::Day21>(2020, 21),
+    heavy::<aoc2020_day22::Day22>(2020, 22),
+    heavy::<aoc2020_day23::Day23>(2020, 23),
+    light::<aoc2020_day24::Day24>(2020, 24),
+    light::<aoc2020_day25::Day25>(2020, 25),
+    // aoc new: days
+];
@@ -0,0 +1,311 @@
+use std::fs;
+use std::path::Path;
+
+const DAYS_MARKER: &str = "// aoc new: days";
+const YEARS_MARKER: &str = "// aoc new: years";
+const REGISTRY_DIR: &str = "aoc/src/registry";
+
+// Creates <year>/dayNN as a crate implementing `Solution`, then wires it into the
+// workspace, the runner's dependencies and the year's registry. Returns every path touched,
+// relative to the workspace root.
+pub fn new_day(root: &Path, year: u16, day: u8) -> Result<Vec<String>, String> {
+    if !read(&root.join("Cargo.toml"))?.contains("[workspace]") {
+        return Err("aoc new must be run from the workspace root".into());
+    }
+
+    let crate_dir = format!("{}/day{:02}", year, day);
+    if root.join(&crate_dir).exists() {
+        return Err(format!("{} already exists", crate_dir));
+    }
+    let package = format!("aoc{}-day{:02}", year, day);
+    let lib_name = package.replace('-', "_");
+
+    let mut touched = vec![];
+    let crate_path = root.join(&crate_dir);
+    write(&crate_path.join("Cargo.toml"), &manifest(&package))?;
+    write(&crate_path.join("src/lib.rs"), &lib_template(day))?;
+    write(
+        &crate_path.join("src/main.rs"),
+        &main_template(&lib_name, year, day),
+    )?;
+    touched.push(crate_dir.clone());
+
+    add_workspace_member(&root.join("Cargo.toml"), &crate_dir)?;
+    touched.push("Cargo.toml".into());
+
+    add_dependency(
+        &root.join("aoc/Cargo.toml"),
+        &format!("{} = {{ path = \"../{}\" }}", package, crate_dir),
+    )?;
+    touched.push("aoc/Cargo.toml".into());
+
+    let year_registry = format!("{}/y{}.rs", REGISTRY_DIR, year);
+    if !root.join(&year_registry).exists() {
+        write(&root.join(&year_registry), &year_registry_template())?;
+        let registry_mod = format!("{}/mod.rs", REGISTRY_DIR);
+        insert_after_last(
+            &root.join(&registry_mod),
+            "mod y",
+            &format!("mod y{};", year),
+        )?;
+        insert_before(
+            &root.join(&registry_mod),
+            YEARS_MARKER,
+            &format!("    ({}, y{}::DAYS),", year, year),
+        )?;
+        touched.push(registry_mod);
+    }
+    insert_before(
+        &root.join(&year_registry),
+        DAYS_MARKER,
+        &format!("    light::<{}::Day{}>({}, {}),", lib_name, day, year, day),
+    )?;
+    touched.push(year_registry);
+
+    let input_dir = root.join(format!("input/{}", year));
+    fs::create_dir_all(&input_dir).map_err(|e| format!("{}: {}", input_dir.display(), e))?;
+
+    Ok(touched)
+}
+
+fn manifest(package: &str) -> String {
+    format!(
+        r#"[package]
+name = "{}"
+version = "0.1.0"
+authors = ["reidswan"]
+edition = "2018"
+
+# See more keys and their definitions at https://doc.rust-lang.org/cargo/reference/manifest.html
+
+[dependencies]
+common = {{ path = "../../common" }}
+"#,
+        package
+    )
+}
+
+fn lib_template(day: u8) -> String {
+    format!(
+        r#"use common::parse_vec;
+use common::solution::{{Solution, Unsolved}};
+
+pub struct Day{day};
+
+impl Solution for Day{day} {{
+    type Input = Vec<String>;
+    type Part1 = Unsolved;
+    type Part2 = Unsolved;
+
+    fn parse(src: &str) -> Result<Vec<String>, String> {{
+        parse_vec(src)
+    }}
+
+    fn part1(_input: &Vec<String>) -> Unsolved {{
+        Unsolved
+    }}
+
+    fn part2(_input: &Vec<String>) -> Unsolved {{
+        Unsolved
+    }}
+}}
+"#,
+        day = day
+    )
+}
+
+fn main_template(lib_name: &str, year: u16, day: u8) -> String {
+    format!(
+        r#"use {lib_name}::Day{day};
+use common::solution::run_main;
+
+fn main() {{
+    run_main::<Day{day}>({year}, {day});
+}}
+"#,
+        lib_name = lib_name,
+        year = year,
+        day = day
+    )
+}
+
+fn year_registry_template() -> String {
+    format!(
+        r#"use super::{{light, Entry}};
+
+pub const DAYS: &[Entry] = &[
+    {}
+];
+"#,
+        DAYS_MARKER
+    )
+}
+
+fn add_workspace_member(path: &Path, crate_dir: &str) -> Result<(), String> {
+    let mut src = read(path)?;
+    let members_start = src
+        .find("members = [")
+        .ok_or("Cargo.toml has no workspace members list")?;
+    let members_end = members_start
+        + src[members_start..]
+            .find("\n]")
+            .ok_or("Cargo.toml's members list is not closed")?;
+
+    let before = src[..members_end].trim_end();
+    let separator = if before.ends_with(',') || before.ends_with('[') {
+        ""
+    } else {
+        ","
+    };
+    let insert_at = before.len();
+    src.insert_str(insert_at, &format!("{}\n    \"{}\"", separator, crate_dir));
+
+    write(path, &src)
+}
+
+// adds the line at the end of the [dependencies] table, wherever that is in the manifest
+fn add_dependency(path: &Path, line: &str) -> Result<(), String> {
+    let mut src = read(path)?;
+    let header = src
+        .find("[dependencies]\n")
+        .ok_or(format!("{} has no [dependencies] table", path.display()))?;
+    let table_start = header + "[dependencies]\n".len();
+    let table_end = src[table_start..]
+        .find("\n[")
+        .map_or(src.len(), |i| table_start + i + 1);
+    let insert_at = table_start + src[table_start..table_end].trim_end().len();
+    if insert_at == table_start {
+        src.insert_str(insert_at, &format!("{}\n", line));
+    } else {
+        src.insert_str(insert_at, &format!("\n{}", line));
+    }
+
+    write(path, &src)
+}
+
+fn insert_before(path: &Path, marker: &str, line: &str) -> Result<(), String> {
+    let mut src = read(path)?;
+    let marker_at = src.find(marker).ok_or(format!(
+        "{} is missing the '{}' marker",
+        path.display(),
+        marker
+    ))?;
+    let line_start = src[..marker_at].rfind('\n').map_or(0, |i| i + 1);
+    src.insert_str(line_start, &format!("{}\n", line));
+
+    write(path, &src)
+}
+
+fn insert_after_last(path: &Path, prefix: &str, line: &str) -> Result<(), String> {
+    let mut src = read(path)?;
+    let last = src.lines().rfind(|l| l.starts_with(prefix)).ok_or(format!(
+        "{} has no line starting with '{}'",
+        path.display(),
+        prefix
+    ))?;
+    let line_end = src.find(last).unwrap() + last.len();
+    src.insert_str(line_end, &format!("\n{}", line));
+
+    write(path, &src)
+}
+
+fn read(path: &Path) -> Result<String, String> {
+    fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+fn write(path: &Path, contents: &str) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("{}: {}", parent.display(), e))?;
+    }
+    fs::write(path, contents).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let path =
+                std::env::temp_dir().join(format!("aoc-scaffold-{}-{}", std::process::id(), name));
+            let _ = fs::remove_dir_all(&path);
+            fs::create_dir_all(&path).unwrap();
+            TempDir(path)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn workspace(name: &str) -> TempDir {
+        let dir = TempDir::new(name);
+        let files = [
+            (
+                "Cargo.toml",
+                "[workspace]\nmembers = [\n    \"aoc\",\n    \"common\",\n]\n",
+            ),
+            (
+                "aoc/Cargo.toml",
+                "[package]\nname = \"aoc\"\n\n[dependencies]\ncommon = { path = \"../common\" }\n\n[features]\ntrace = []\n",
+            ),
+            (
+                "aoc/src/registry/mod.rs",
+                "mod y2020;\n\npub const YEARS: &[(u16, &[Entry])] = &[\n    (2020, y2020::DAYS),\n    // aoc new: years\n];\n",
+            ),
+        ];
+        for (path, contents) in files.iter() {
+            write(&dir.0.join(path), contents).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn new_day_wires_up_a_new_year() {
+        let dir = workspace("new-year");
+        let touched = new_day(&dir.0, 2021, 3).unwrap();
+        assert!(touched.contains(&"2021/day03".to_string()));
+
+        let root = read(&dir.0.join("Cargo.toml")).unwrap();
+        assert!(root.contains("    \"common\",\n    \"2021/day03\"\n]"));
+        let runner = read(&dir.0.join("aoc/Cargo.toml")).unwrap();
+        assert!(runner.contains(
+            "common = { path = \"../common\" }\naoc2021-day03 = { path = \"../2021/day03\" }\n\n[features]"
+        ));
+        let registry = read(&dir.0.join("aoc/src/registry/mod.rs")).unwrap();
+        assert!(registry.contains("mod y2020;\nmod y2021;"));
+        assert!(registry.contains("    (2021, y2021::DAYS),\n    // aoc new: years"));
+        let days = read(&dir.0.join("aoc/src/registry/y2021.rs")).unwrap();
+        assert!(days.contains("    light::<aoc2021_day03::Day3>(2021, 3),\n    // aoc new: days"));
+        assert!(dir.0.join("2021/day03/src/lib.rs").exists());
+        assert!(dir.0.join("input/2021").is_dir());
+    }
+
+    #[test]
+    fn new_day_refuses_an_existing_day() {
+        let dir = workspace("existing");
+        new_day(&dir.0, 2021, 3).unwrap();
+        assert_eq!(
+            new_day(&dir.0, 2021, 3).unwrap_err(),
+            "2021/day03 already exists"
+        );
+    }
+
+    #[test]
+    fn dependencies_go_in_their_table() {
+        let dir = TempDir::new("dependencies");
+        let path = dir.0.join("Cargo.toml");
+        write(&path, "[dependencies]\n\n[dev-dependencies]\nx = \"1\"\n").unwrap();
+        add_dependency(&path, "a = \"1\"").unwrap();
+        add_dependency(&path, "b = \"2\"").unwrap();
+        assert_eq!(
+            read(&path).unwrap(),
+            "[dependencies]\na = \"1\"\nb = \"2\"\n\n[dev-dependencies]\nx = \"1\"\n"
+        );
+    }
+}
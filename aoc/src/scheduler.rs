@@ -0,0 +1,100 @@
+use crate::registry::Entry;
+use common::solution::Report;
+use std::collections::BTreeMap;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::channel;
+use std::time::{Duration, Instant};
+
+// day22's recursive combat recurses once per round, which needs more than rayon's
+// default worker stack; match the main thread's usual 8MB
+const WORKER_STACK_SIZE: usize = 8 * 1024 * 1024;
+
+// inputs are resolved before scheduling, so fetching never happens on the pool
+pub struct Job {
+    pub entry: &'static Entry,
+    pub input: Result<String, String>,
+}
+
+pub struct Outcome {
+    pub entry: &'static Entry,
+    pub result: Result<Report, String>,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Timings {
+    pub wall: Duration,
+    pub cpu: Duration,
+}
+
+pub fn init_pool(jobs: usize) -> Result<(), String> {
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(jobs)
+        .stack_size(WORKER_STACK_SIZE)
+        .thread_name(|idx| format!("aoc-worker-{}", idx))
+        .build_global()
+        .map_err(|e| format!("Failed to start the worker pool: {}", e))
+}
+
+// Runs every entry against its input on the global pool, heaviest first, and hands the
+// outcomes to `on_complete` in the order the entries were given, as soon as each is available.
+pub fn run_in_order<F>(jobs: Vec<Job>, mut on_complete: F) -> Timings
+where
+    F: FnMut(Outcome),
+{
+    let wall_start = Instant::now();
+    let cpu_start = cpu_time();
+
+    let mut schedule: Vec<_> = jobs.into_iter().enumerate().collect();
+    schedule.sort_by_key(|(idx, job)| (std::cmp::Reverse(job.entry.cost), *idx));
+
+    let (tx, rx) = channel();
+    for (idx, Job { entry, input }) in schedule {
+        let tx = tx.clone();
+        rayon::spawn(move || {
+            let result = input.and_then(|src| run_entry(entry, &src));
+            // the receiver only goes away once every result is in
+            let _ = tx.send((idx, Outcome { entry, result }));
+        });
+    }
+    drop(tx);
+
+    let mut pending = BTreeMap::new();
+    let mut next = 0;
+    for (idx, outcome) in rx {
+        pending.insert(idx, outcome);
+        while let Some(outcome) = pending.remove(&next) {
+            on_complete(outcome);
+            next += 1;
+        }
+    }
+
+    Timings {
+        wall: wall_start.elapsed(),
+        cpu: cpu_time() - cpu_start,
+    }
+}
+
+fn run_entry(entry: &Entry, src: &str) -> Result<Report, String> {
+    let _span = common::span!(Info, "day", year = entry.year, day = entry.day);
+    let result = panic::catch_unwind(AssertUnwindSafe(|| (entry.run)(src)))
+        .unwrap_or_else(|_| Err("Solver panicked".into()));
+    if let Err(e) = &result {
+        common::event!(Error, "failed", error = &e[..]);
+    }
+    result
+}
+
+// user + system time across every thread in the process
+fn cpu_time() -> Duration {
+    let mut usage = std::mem::MaybeUninit::<libc::rusage>::uninit();
+    // SAFETY: getrusage only writes into the struct we hand it
+    let usage = unsafe {
+        if libc::getrusage(libc::RUSAGE_SELF, usage.as_mut_ptr()) != 0 {
+            return Duration::default();
+        }
+        usage.assume_init()
+    };
+    let to_duration = |tv: libc::timeval| Duration::new(tv.tv_sec as u64, tv.tv_usec as u32 * 1000);
+
+    to_duration(usage.ru_utime) + to_duration(usage.ru_stime)
+}
@@ -0,0 +1,243 @@
+use crate::solution::{load_input, load_input_file, run_main, Solution};
+use std::str::FromStr;
+
+/// What a day binary accepts beyond its commands: the usage text shown with argument
+/// errors, flags that stand alone, and options that take a value, each with a word for
+/// what the value is. Every binary takes `--input FILE` without listing it.
+pub struct Cli {
+    pub usage: &'static str,
+    pub switches: &'static [&'static str],
+    pub options: &'static [(&'static str, &'static str)],
+}
+
+/// The arguments after the command: everything that isn't a flag, in order, and the
+/// flags that were given.
+pub struct Args<'a> {
+    pub positional: Vec<&'a str>,
+    cli: &'a Cli,
+    raw: &'a [String],
+    switches: Vec<&'a str>,
+    // every option given, in order, with its value
+    values: Vec<(&'a str, &'a str)>,
+}
+
+const INPUT: (&str, &str) = ("--input", "a file");
+
+// With no arguments, solves the day's puzzle; otherwise hands the command and its
+// arguments to `run`, reporting any error and exiting with status 1.
+pub fn main<S: Solution>(
+    year: u16,
+    day: u8,
+    cli: &Cli,
+    run: fn(&str, &Args) -> Result<(), String>,
+) {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (command, rest) = match args.split_first() {
+        Some(split) => split,
+        None => {
+            run_main::<S>(year, day);
+            return;
+        }
+    };
+    let result = Args::parse(cli, rest)
+        .map_err(|e| usage_error(cli.usage, e))
+        .and_then(|args| run(command, &args));
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
+
+pub fn usage_error(usage: &str, e: String) -> String {
+    format!("{}\n{}", e, usage)
+}
+
+impl<'a> Args<'a> {
+    pub fn parse(cli: &'a Cli, raw: &'a [String]) -> Result<Args<'a>, String> {
+        let mut args = Args {
+            positional: vec![],
+            cli,
+            raw,
+            switches: vec![],
+            values: vec![],
+        };
+        let mut rest = raw.iter();
+        while let Some(arg) = rest.next() {
+            let arg = &arg[..];
+            if cli.switches.contains(&arg) {
+                args.switches.push(arg);
+            } else if let Some((name, what)) = std::iter::once(&INPUT)
+                .chain(cli.options)
+                .find(|(name, _)| *name == arg)
+            {
+                let value = rest
+                    .next()
+                    .ok_or(format!("Expected {} after {}", what, name))?;
+                args.values.push((name, value));
+            } else if arg.starts_with("--") {
+                return Err(format!("Unknown option: {}", arg));
+            } else {
+                args.positional.push(arg);
+            }
+        }
+        Ok(args)
+    }
+
+    pub fn switch(&self, name: &str) -> bool {
+        self.switches.contains(&name)
+    }
+
+    // the last value given for the option
+    pub fn value(&self, name: &str) -> Option<&'a str> {
+        self.values(name).pop()
+    }
+
+    pub fn values(&self, name: &str) -> Vec<&'a str> {
+        self.values
+            .iter()
+            .filter(|(option, _)| *option == name)
+            .map(|&(_, value)| value)
+            .collect()
+    }
+
+    pub fn parsed<T: FromStr>(&self, name: &str) -> Result<Option<T>, String> {
+        self.value(name)
+            .map(|value| T::from_str(value).map_err(|_| format!("Invalid {}: {}", name, value)))
+            .transpose()
+    }
+
+    // a positional argument, named for the error if it doesn't parse
+    pub fn parse_arg<T: FromStr>(&self, value: &str, name: &str) -> Result<T, String> {
+        T::from_str(value).map_err(|_| self.usage_error(format!("Invalid {}: {}", name, value)))
+    }
+
+    // the option's value, which has to be one of `choices`
+    pub fn choice(&self, name: &str, choices: &[&'a str]) -> Result<Option<&'a str>, String> {
+        match self.value(name) {
+            Some(value) if !choices.contains(&value) => {
+                Err(self.usage_error(format!("Expected {} after {}", choices.join(" or "), name)))
+            }
+            value => Ok(value),
+        }
+    }
+
+    pub fn input(&self) -> Option<&'a str> {
+        self.value(INPUT.0)
+    }
+
+    // the --input file, or else the day's own input
+    pub fn load<S: Solution>(&self, year: u16, day: u8) -> Result<S::Input, String> {
+        match self.input() {
+            Some(file_name) => load_input_file::<S>(file_name),
+            None => load_input::<S>(year, day),
+        }
+    }
+
+    pub fn usage_error(&self, e: String) -> String {
+        usage_error(self.cli.usage, e)
+    }
+
+    pub fn unknown_command(&self, command: &str) -> String {
+        let mut words = vec![command];
+        words.extend(self.raw.iter().map(|arg| &arg[..]));
+        self.usage_error(format!("Unknown command: {}", words.join(" ")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CLI: Cli = Cli {
+        usage: "Usage: day <command>",
+        switches: &["--verbose"],
+        options: &[("--slope", "a slope"), ("--format", "a format")],
+    };
+
+    fn raw(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn separates_switches_options_and_positionals() {
+        let raw = raw(&["a", "--verbose", "--slope", "3", "b", "--slope", "5"]);
+        let args = Args::parse(&CLI, &raw).unwrap();
+        assert_eq!(args.positional, vec!["a", "b"]);
+        assert!(args.switch("--verbose"));
+        assert!(!args.switch("--quiet"));
+        assert_eq!(args.values("--slope"), vec!["3", "5"]);
+        assert_eq!(args.value("--slope"), Some("5"));
+        assert_eq!(args.value("--format"), None);
+        assert_eq!(args.input(), None);
+    }
+
+    #[test]
+    fn every_binary_takes_an_input_file() {
+        let raw = raw(&["--input", "puzzle.txt"]);
+        let args = Args::parse(&CLI, &raw).unwrap();
+        assert_eq!(args.input(), Some("puzzle.txt"));
+        assert!(args.positional.is_empty());
+    }
+
+    #[test]
+    fn options_need_a_value() {
+        let raw = raw(&["a", "--slope"]);
+        assert_eq!(
+            Args::parse(&CLI, &raw).err(),
+            Some("Expected a slope after --slope".to_string())
+        );
+        let raw = self::raw(&["--input"]);
+        assert_eq!(
+            Args::parse(&CLI, &raw).err(),
+            Some("Expected a file after --input".to_string())
+        );
+    }
+
+    #[test]
+    fn unknown_options_are_errors() {
+        let raw = raw(&["--slop", "3"]);
+        assert_eq!(
+            Args::parse(&CLI, &raw).err(),
+            Some("Unknown option: --slop".to_string())
+        );
+    }
+
+    #[test]
+    fn parses_values_or_reports_them() {
+        let raw = raw(&["--slope", "3", "--format", "x"]);
+        let args = Args::parse(&CLI, &raw).unwrap();
+        assert_eq!(args.parsed::<u32>("--slope"), Ok(Some(3)));
+        assert_eq!(args.parsed::<u32>("--verbose"), Ok(None));
+        assert_eq!(
+            args.parsed::<u32>("--format"),
+            Err("Invalid --format: x".to_string())
+        );
+        assert_eq!(args.parse_arg::<u8>("7", "day"), Ok(7));
+        assert_eq!(
+            args.parse_arg::<u8>("seven", "day"),
+            Err("Invalid day: seven\nUsage: day <command>".to_string())
+        );
+    }
+
+    #[test]
+    fn choices_are_checked() {
+        let raw = raw(&["--format", "csv"]);
+        let args = Args::parse(&CLI, &raw).unwrap();
+        assert_eq!(args.choice("--format", &["csv", "ndjson"]), Ok(Some("csv")));
+        assert_eq!(args.choice("--slope", &["csv", "ndjson"]), Ok(None));
+        assert_eq!(
+            args.choice("--format", &["text", "json"]),
+            Err("Expected text or json after --format\nUsage: day <command>".to_string())
+        );
+    }
+
+    #[test]
+    fn unknown_commands_repeat_the_arguments() {
+        let raw = raw(&["a", "--verbose"]);
+        let args = Args::parse(&CLI, &raw).unwrap();
+        assert_eq!(
+            args.unknown_command("frobnicate"),
+            "Unknown command: frobnicate a --verbose\nUsage: day <command>"
+        );
+    }
+}
@@ -0,0 +1,63 @@
+use std::fmt::Write;
+
+// only quoted when it has to be, with any quotes inside doubled
+pub fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.into()
+    }
+}
+
+pub fn json_str(s: &str) -> String {
+    let mut out = String::new();
+    push_json_str(&mut out, s);
+    out
+}
+
+// the string quoted and escaped as a JSON string literal
+pub fn push_json_str(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_field_quotes_only_when_needed() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field(""), "");
+        assert_eq!(csv_field("a,b"), r#""a,b""#);
+        assert_eq!(csv_field(r#"say "hi""#), r#""say ""hi""""#);
+        assert_eq!(csv_field("one\ntwo"), "\"one\ntwo\"");
+        assert_eq!(csv_field("one\r\ntwo"), "\"one\r\ntwo\"");
+        // other control characters and non-ASCII text pass through as they are
+        assert_eq!(csv_field("tab\there\u{1}"), "tab\there\u{1}");
+        assert_eq!(csv_field("café ☃"), "café ☃");
+        assert_eq!(csv_field("naïve, \"ü\""), "\"naïve, \"\"ü\"\"\"");
+    }
+
+    #[test]
+    fn json_str_escapes_what_json_needs() {
+        assert_eq!(json_str("plain"), r#""plain""#);
+        assert_eq!(json_str(r#"say "hi""#), r#""say \"hi\"""#);
+        assert_eq!(json_str(r"a\b"), r#""a\\b""#);
+        assert_eq!(json_str("a,b"), r#""a,b""#);
+        assert_eq!(json_str("one\ntwo\r\n\tthree"), r#""one\ntwo\r\n\tthree""#);
+        assert_eq!(json_str("\u{0}\u{1b}\u{1f}"), r#""\u0000\u001b\u001f""#);
+        assert_eq!(json_str("café ☃ 🎄"), "\"café ☃ 🎄\"");
+    }
+}
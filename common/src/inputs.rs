@@ -0,0 +1,430 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+pub const DEFAULT_CACHE_DIR: &str = "input";
+const CHECKSUMS_FILE: &str = "checksums.txt";
+
+/// Somewhere a missing puzzle input can be fetched from.
+pub trait InputProvider {
+    fn fetch(&self, year: u16, day: u8) -> Result<String, String>;
+}
+
+// another input directory laid out like the cache, e.g. a second checkout's input/
+pub struct DirectoryProvider {
+    root: PathBuf,
+}
+
+impl DirectoryProvider {
+    pub fn new<P: Into<PathBuf>>(root: P) -> Self {
+        DirectoryProvider { root: root.into() }
+    }
+}
+
+impl InputProvider for DirectoryProvider {
+    fn fetch(&self, year: u16, day: u8) -> Result<String, String> {
+        let path = day_path(&self.root, year, day);
+        fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+}
+
+#[cfg(feature = "http")]
+pub use http::{HttpProvider, DEFAULT_BASE_URL};
+
+#[cfg(feature = "http")]
+mod http {
+    use super::InputProvider;
+    use std::time::Duration;
+
+    pub const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+    const USER_AGENT: &str = concat!("aoc-input-cache/", env!("CARGO_PKG_VERSION"));
+
+    // GETs <base_url>/<year>/day/<day>/input with the account's session cookie
+    pub struct HttpProvider {
+        base_url: String,
+        session: String,
+        agent: ureq::Agent,
+    }
+
+    impl HttpProvider {
+        pub fn new(base_url: &str, session: &str) -> Self {
+            HttpProvider {
+                base_url: base_url.trim_end_matches('/').into(),
+                session: session.trim().into(),
+                agent: ureq::AgentBuilder::new()
+                    .timeout(Duration::from_secs(30))
+                    .user_agent(USER_AGENT)
+                    .build(),
+            }
+        }
+    }
+
+    impl InputProvider for HttpProvider {
+        fn fetch(&self, year: u16, day: u8) -> Result<String, String> {
+            let url = format!("{}/{}/day/{}/input", self.base_url, year, day);
+            let response = self
+                .agent
+                .get(&url)
+                .set("Cookie", &format!("session={}", self.session))
+                .call()
+                .map_err(|e| match e {
+                    ureq::Error::Status(status, _) => format!("{}: HTTP {}", url, status),
+                    ureq::Error::Transport(e) => format!("{}: {}", url, e),
+                })?;
+            response
+                .into_string()
+                .map_err(|e| format!("{}: {}", url, e))
+        }
+    }
+}
+
+/// Resolves (year, day) to the cached input under `<root>/<year>/day<day>.txt`, fetching
+/// it through the provider when it is missing. Cached files are made read-only and their
+/// checksums kept in `<root>/<year>/checksums.txt`, so that later edits are reported
+/// instead of silently changing the answers.
+pub struct InputCache {
+    root: PathBuf,
+    provider: Option<Box<dyn InputProvider>>,
+}
+
+impl InputCache {
+    // offline until a provider is added
+    pub fn new<P: Into<PathBuf>>(root: P) -> Self {
+        InputCache {
+            root: root.into(),
+            provider: None,
+        }
+    }
+
+    // AOC_INPUT_DIR picks a directory provider; otherwise, with the http feature,
+    // AOC_SESSION (and optionally AOC_BASE_URL) picks the HTTP one. AOC_OFFLINE wins over both.
+    pub fn from_env() -> Self {
+        let cache = InputCache::new(DEFAULT_CACHE_DIR);
+        if env::var_os("AOC_OFFLINE").is_some() {
+            return cache;
+        }
+        if let Some(dir) = env::var_os("AOC_INPUT_DIR") {
+            return cache.with_provider(DirectoryProvider::new(dir));
+        }
+        #[cfg(feature = "http")]
+        {
+            if let Ok(session) = env::var("AOC_SESSION") {
+                let base_url = env::var("AOC_BASE_URL").unwrap_or_else(|_| DEFAULT_BASE_URL.into());
+                return cache.with_provider(HttpProvider::new(&base_url, &session));
+            }
+        }
+        cache
+    }
+
+    pub fn with_provider<P: InputProvider + 'static>(mut self, provider: P) -> Self {
+        self.provider = Some(Box::new(provider));
+        self
+    }
+
+    pub fn offline(mut self) -> Self {
+        self.provider = None;
+        self
+    }
+
+    pub fn path(&self, year: u16, day: u8) -> PathBuf {
+        day_path(&self.root, year, day)
+    }
+
+    pub fn get(&self, year: u16, day: u8) -> Result<String, String> {
+        let path = self.path(year, day);
+        match fs::read_to_string(&path) {
+            Ok(src) => {
+                // inputs that were put in place by hand are adopted the first time they're read
+                if !self.verify(year, day, &path, &src)? {
+                    self.record(year, day, &path, &src)?;
+                }
+                Ok(src)
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                let provider = self.provider.as_ref().ok_or(format!(
+                    "{} is not cached and no input provider is configured",
+                    path.display()
+                ))?;
+                let src = provider.fetch(year, day)?;
+                self.store(year, day, &path, &src)?;
+                Ok(src)
+            }
+            Err(e) => Err(format!("{}: {}", path.display(), e)),
+        }
+    }
+
+    // false if no checksum has been recorded for the input yet; never writes anything
+    fn verify(&self, year: u16, day: u8, path: &Path, src: &str) -> Result<bool, String> {
+        let checksums = self.checksums(year)?;
+        let actual = checksum(src);
+        match checksums.known.get(&day) {
+            Some(&expected) if expected == actual => Ok(true),
+            Some(&expected) => Err(format!(
+                "{} has changed since it was cached (checksum {:016x}, expected {:016x}); \
+                 restore it, or delete it to fetch it again",
+                path.display(),
+                actual,
+                expected
+            )),
+            None => Ok(false),
+        }
+    }
+
+    // makes the cached input read-only and remembers its checksum
+    fn record(&self, year: u16, day: u8, path: &Path, src: &str) -> Result<(), String> {
+        set_readonly(path)?;
+        let mut checksums = self.checksums(year)?;
+        checksums.known.insert(day, checksum(src));
+        self.save_checksums(year, &checksums)
+    }
+
+    fn store(&self, year: u16, day: u8, path: &Path, src: &str) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("{}: {}", parent.display(), e))?;
+        }
+        fs::write(path, src).map_err(|e| format!("{}: {}", path.display(), e))?;
+        self.record(year, day, path, src)
+    }
+
+    fn checksums(&self, year: u16) -> Result<Checksums, String> {
+        let path = self.checksums_path(year);
+        match fs::read_to_string(&path) {
+            Ok(src) => Checksums::from_str(&src).map_err(|e| format!("{}: {}", path.display(), e)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Checksums::default()),
+            Err(e) => Err(format!("{}: {}", path.display(), e)),
+        }
+    }
+
+    fn save_checksums(&self, year: u16, checksums: &Checksums) -> Result<(), String> {
+        let path = self.checksums_path(year);
+        fs::write(&path, checksums.to_string()).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    fn checksums_path(&self, year: u16) -> PathBuf {
+        self.root.join(year.to_string()).join(CHECKSUMS_FILE)
+    }
+}
+
+fn day_path(root: &Path, year: u16, day: u8) -> PathBuf {
+    root.join(year.to_string()).join(format!("day{}.txt", day))
+}
+
+fn set_readonly(path: &Path) -> Result<(), String> {
+    let mut permissions = fs::metadata(path)
+        .map_err(|e| format!("{}: {}", path.display(), e))?
+        .permissions();
+    permissions.set_readonly(true);
+    fs::set_permissions(path, permissions).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+// 64-bit FNV-1a: only has to notice accidental edits, not tampering
+fn checksum(src: &str) -> u64 {
+    src.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+// one `<day> <checksum>` line per cached input
+#[derive(Debug, Default)]
+struct Checksums {
+    known: BTreeMap<u8, u64>,
+}
+
+impl FromStr for Checksums {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut known = BTreeMap::new();
+        for (idx, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (day, sum) = line
+                .split_once(' ')
+                .ok_or(format!("line {}: expected <day> <checksum>", idx + 1))?;
+            let day = u8::from_str(day).map_err(|e| format!("line {}: {}", idx + 1, e))?;
+            let sum = u64::from_str_radix(sum.trim(), 16)
+                .map_err(|e| format!("line {}: {}", idx + 1, e))?;
+            known.insert(day, sum);
+        }
+
+        Ok(Checksums { known })
+    }
+}
+
+impl std::fmt::Display for Checksums {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "# <day> <checksum>, written by the input cache")?;
+        for (day, sum) in self.known.iter() {
+            writeln!(f, "{} {:016x}", day, sum)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a fresh directory under the system temp dir, removed when dropped
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let path = env::temp_dir().join(format!("aoc-inputs-{}-{}", std::process::id(), name));
+            let _ = fs::remove_dir_all(&path);
+            fs::create_dir_all(&path).unwrap();
+            TempDir(path)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn write_input(root: &Path, year: u16, day: u8, src: &str) -> PathBuf {
+        let path = day_path(root, year, day);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, src).unwrap();
+        path
+    }
+
+    #[test]
+    fn fetches_and_caches_missing_inputs() {
+        let (cache_dir, source_dir) = (TempDir::new("fetch-cache"), TempDir::new("fetch-source"));
+        write_input(&source_dir.0, 2020, 1, "1721\n979\n");
+
+        let cache =
+            InputCache::new(&cache_dir.0).with_provider(DirectoryProvider::new(&source_dir.0));
+        assert_eq!(cache.get(2020, 1).unwrap(), "1721\n979\n");
+        let path = cache.path(2020, 1);
+        assert!(fs::metadata(&path).unwrap().permissions().readonly());
+        let checksums = fs::read_to_string(cache_dir.0.join("2020").join(CHECKSUMS_FILE)).unwrap();
+        assert!(checksums.contains(&format!("1 {:016x}", checksum("1721\n979\n"))));
+
+        // the cached copy is used from then on
+        fs::remove_dir_all(&source_dir.0).unwrap();
+        let offline = InputCache::new(&cache_dir.0);
+        assert_eq!(offline.get(2020, 1).unwrap(), "1721\n979\n");
+    }
+
+    #[test]
+    fn missing_input_without_a_provider_is_an_error() {
+        let dir = TempDir::new("missing");
+        let e = InputCache::new(&dir.0).get(2020, 2).unwrap_err();
+        assert!(e.contains("no input provider is configured"), "{}", e);
+    }
+
+    #[test]
+    fn adopts_inputs_put_in_place_by_hand() {
+        let dir = TempDir::new("adopt");
+        let path = write_input(&dir.0, 2020, 3, "..#\n");
+        let cache = InputCache::new(&dir.0);
+
+        assert!(!cache.verify(2020, 3, &path, "..#\n").unwrap());
+        // checking alone doesn't touch anything
+        assert!(!dir.0.join("2020").join(CHECKSUMS_FILE).exists());
+        assert!(!fs::metadata(&path).unwrap().permissions().readonly());
+
+        assert_eq!(cache.get(2020, 3).unwrap(), "..#\n");
+        assert!(cache.verify(2020, 3, &path, "..#\n").unwrap());
+        assert!(fs::metadata(&path).unwrap().permissions().readonly());
+    }
+
+    #[test]
+    fn reports_inputs_changed_after_caching() {
+        let dir = TempDir::new("changed");
+        let path = write_input(&dir.0, 2020, 4, "ecl:gry\n");
+        let cache = InputCache::new(&dir.0);
+        cache.get(2020, 4).unwrap();
+
+        let mut permissions = fs::metadata(&path).unwrap().permissions();
+        #[allow(clippy::permissions_set_readonly_false)]
+        permissions.set_readonly(false);
+        fs::set_permissions(&path, permissions).unwrap();
+        fs::write(&path, "ecl:blu\n").unwrap();
+
+        let e = cache.get(2020, 4).unwrap_err();
+        assert!(e.contains("has changed since it was cached"), "{}", e);
+    }
+
+    #[cfg(feature = "http")]
+    mod http {
+        use super::*;
+        use std::io::{BufRead, BufReader, Write};
+        use std::net::TcpListener;
+        use std::thread::{self, JoinHandle};
+
+        // answers a single request with the given status and body, handing back the
+        // request's head
+        fn serve_once(status: &str, body: &'static str) -> (String, JoinHandle<Vec<String>>) {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let base_url = format!("http://{}", listener.local_addr().unwrap());
+            let status = status.to_string();
+            let handle = thread::spawn(move || {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut head = vec![];
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    let line = line.trim_end().to_string();
+                    if line.is_empty() {
+                        break;
+                    }
+                    head.push(line);
+                }
+                let mut stream = stream;
+                write!(
+                    stream,
+                    "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                )
+                .unwrap();
+                head
+            });
+            (base_url, handle)
+        }
+
+        #[test]
+        fn fetches_over_http_with_the_session_cookie() {
+            let dir = TempDir::new("http");
+            let (base_url, server) = serve_once("200 OK", "nop +0\n");
+            let cache =
+                InputCache::new(&dir.0).with_provider(HttpProvider::new(&base_url, "abc123\n"));
+
+            assert_eq!(cache.get(2020, 8).unwrap(), "nop +0\n");
+            let head = server.join().unwrap();
+            assert_eq!(head[0], "GET /2020/day/8/input HTTP/1.1");
+            assert!(head
+                .iter()
+                .any(|line| line.eq_ignore_ascii_case("cookie: session=abc123")));
+            assert!(fs::metadata(cache.path(2020, 8))
+                .unwrap()
+                .permissions()
+                .readonly());
+        }
+
+        #[test]
+        fn http_errors_are_reported_and_not_cached() {
+            let dir = TempDir::new("http-404");
+            let (base_url, server) = serve_once("404 Not Found", "");
+            let cache =
+                InputCache::new(&dir.0).with_provider(HttpProvider::new(&base_url, "abc123"));
+
+            let e = cache.get(2020, 9).unwrap_err();
+            server.join().unwrap();
+            assert!(e.contains("HTTP 404"), "{}", e);
+            assert!(!cache.path(2020, 9).exists());
+        }
+    }
+}
@@ -1,3 +1,10 @@
+pub mod cli;
+pub mod escape;
+pub mod inputs;
+pub mod memory;
+pub mod solution;
+pub mod trace;
+
 use std::iter::Peekable;
 use std::ops::{Add, Div, Mul, Rem, Sub};
 use std::str::Chars;
@@ -8,17 +15,8 @@ where
     T: FromStr<Err = E>,
     E: std::fmt::Debug,
 {
-    let raw_input = load_raw_text(file_name);
-    raw_input
-        .split("\n\n")
-        .filter_map(|i| {
-            let trimmed = i.trim();
-            if !trimmed.is_empty() {
-                Some(T::from_str(trimmed).unwrap())
-            } else {
-                None
-            }
-        })
+    groups(&load_raw_text(file_name))
+        .map(|i| T::from_str(i).unwrap())
         .collect()
 }
 
@@ -27,16 +25,7 @@ where
     T: FromStr<Err = E>,
     E: std::fmt::Debug,
 {
-    let raw = load_raw_text(file_name);
-    raw.split('\n')
-        .filter_map(|i| {
-            let trimmed = i.trim();
-            if !trimmed.is_empty() {
-                Some(trimmed)
-            } else {
-                None
-            }
-        })
+    non_empty_lines(&load_raw_text(file_name))
         .map(|s| T::from_str(s).unwrap())
         .collect()
 }
@@ -55,6 +44,53 @@ pub fn load_raw_text(file_name: &str) -> String {
     std::fs::read_to_string(file_name).unwrap()
 }
 
+// inputs and answers live under input/<year>/, relative to the workspace root
+pub fn input_file(year: u16, day: u8) -> String {
+    format!("input/{}/day{}.txt", year, day)
+}
+
+pub fn answers_file(year: u16) -> String {
+    format!("input/{}/answers.txt", year)
+}
+
+pub fn parse_groups<T, E>(raw: &str) -> Result<Vec<T>, String>
+where
+    T: FromStr<Err = E>,
+    E: std::fmt::Display,
+{
+    groups(raw)
+        .map(|i| T::from_str(i).map_err(|e| e.to_string()))
+        .collect()
+}
+
+pub fn parse_vec<T, E>(raw: &str) -> Result<Vec<T>, String>
+where
+    T: FromStr<Err = E>,
+    E: std::fmt::Display,
+{
+    non_empty_lines(raw)
+        .map(|s| T::from_str(s).map_err(|e| e.to_string()))
+        .collect()
+}
+
+pub fn parse_single_object<T, E>(raw: &str) -> Result<T, String>
+where
+    T: FromStr<Err = E>,
+    E: std::fmt::Display,
+{
+    T::from_str(raw.trim()).map_err(|e| e.to_string())
+}
+
+fn groups(raw: &str) -> impl Iterator<Item = &str> {
+    raw.split("\n\n")
+        .map(|i| i.trim())
+        .filter(|i| !i.is_empty())
+}
+
+fn non_empty_lines(raw: &str) -> impl Iterator<Item = &str> {
+    raw.split('\n').map(|i| i.trim()).filter(|i| !i.is_empty())
+}
+
 pub fn take_first_number<T, E>(src: &mut Peekable<Chars<'_>>) -> Result<T, String>
 where
     T: FromStr<Err = E> + Into<usize>,
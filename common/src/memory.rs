@@ -0,0 +1,138 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicBool, AtomicIsize, AtomicU64, Ordering::Relaxed};
+
+/// A `System` wrapper that counts heap traffic once `enable` has been called. Install it in
+/// a binary with `#[global_allocator]`; until it is enabled it costs one relaxed load per call.
+pub struct CountingAllocator;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+// live can go negative when memory allocated before `enable` is freed; only differences matter
+static LIVE: AtomicIsize = AtomicIsize::new(0);
+static PEAK: AtomicIsize = AtomicIsize::new(0);
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+static ALLOCATED: AtomicU64 = AtomicU64::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() && ENABLED.load(Relaxed) {
+            record_alloc(layout.size(), 0);
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() && ENABLED.load(Relaxed) {
+            record_alloc(layout.size(), 0);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        if ENABLED.load(Relaxed) {
+            LIVE.fetch_sub(layout.size() as isize, Relaxed);
+        }
+    }
+
+    // a growing Vec or HashMap reallocates, so count it as a fresh allocation of the new size
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() && ENABLED.load(Relaxed) {
+            record_alloc(new_size, layout.size());
+        }
+        new_ptr
+    }
+}
+
+fn record_alloc(size: usize, freed: usize) {
+    ALLOCATIONS.fetch_add(1, Relaxed);
+    ALLOCATED.fetch_add(size as u64, Relaxed);
+    let delta = size as isize - freed as isize;
+    let live = LIVE.fetch_add(delta, Relaxed) + delta;
+    PEAK.fetch_max(live, Relaxed);
+}
+
+/// Heap traffic while a closure ran. The counters are process-wide, so anything else
+/// allocating at the same time is included.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MemoryUsage {
+    // highest live heap above what was live when the closure started
+    pub peak: usize,
+    pub allocations: u64,
+    pub allocated: u64,
+}
+
+// fails if the binary didn't install `CountingAllocator`, as nothing would ever be counted
+pub fn enable() -> Result<(), String> {
+    ENABLED.store(true, Relaxed);
+    let before = ALLOCATIONS.load(Relaxed);
+    drop(std::hint::black_box(Box::new(0u64)));
+    if ALLOCATIONS.load(Relaxed) == before {
+        ENABLED.store(false, Relaxed);
+        return Err("Memory counting needs CountingAllocator as the global allocator".into());
+    }
+    Ok(())
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Relaxed)
+}
+
+pub fn measure<T, F>(f: F) -> (T, Option<MemoryUsage>)
+where
+    F: FnOnce() -> T,
+{
+    if !is_enabled() {
+        return (f(), None);
+    }
+
+    let live = LIVE.load(Relaxed);
+    PEAK.store(live, Relaxed);
+    let allocations = ALLOCATIONS.load(Relaxed);
+    let allocated = ALLOCATED.load(Relaxed);
+
+    let result = f();
+
+    let usage = MemoryUsage {
+        peak: (PEAK.load(Relaxed) - live).max(0) as usize,
+        allocations: ALLOCATIONS.load(Relaxed) - allocations,
+        allocated: ALLOCATED.load(Relaxed) - allocated,
+    };
+    (result, Some(usage))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    // one test, since the counters are shared by the whole process; other tests running at
+    // the same time can only add to what's counted
+    #[test]
+    fn measures_allocations_once_enabled() {
+        let allocations = ALLOCATIONS.load(Relaxed);
+        let (buffer, usage) = measure(|| vec![0u8; 1 << 20]);
+        assert!(usage.is_none());
+        assert_eq!(ALLOCATIONS.load(Relaxed), allocations);
+        drop(buffer);
+
+        enable().unwrap();
+        assert!(is_enabled());
+        let (buffer, usage) = measure(|| Vec::<u8>::with_capacity(4096));
+        let usage = usage.unwrap();
+        assert!(usage.allocations >= 1);
+        assert!(usage.allocated >= 4096);
+        assert!(usage.peak >= 4096);
+
+        // each measurement starts again from what's live, so freeing doesn't count against it
+        // and the earlier peak is forgotten
+        let (_, usage) = measure(|| drop(buffer));
+        assert!(usage.unwrap().peak < 4096);
+        let (_, usage) = measure(|| drop(std::hint::black_box(vec![0u8; 64])));
+        assert!(usage.unwrap().peak < 4096);
+    }
+}
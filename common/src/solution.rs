@@ -0,0 +1,213 @@
+use crate::inputs::InputCache;
+use crate::memory::{self, MemoryUsage};
+use std::fmt::{self, Display};
+use std::time::{Duration, Instant};
+
+/// A single day's puzzle: parse the raw input once, then answer both parts from it.
+pub trait Solution {
+    type Input;
+    type Part1: Display;
+    type Part2: Display;
+
+    fn parse(src: &str) -> Result<Self::Input, String>;
+
+    fn part1(input: &Self::Input) -> Self::Part1;
+
+    fn part2(input: &Self::Input) -> Self::Part2;
+}
+
+// for days where a part has no answer (day 25) or was never solved
+#[derive(Debug, Clone, Copy)]
+pub struct Unsolved;
+
+impl Display for Unsolved {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "(unsolved)")
+    }
+}
+
+// for parts that can legitimately come up empty, rather than panicking
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Answer<T>(pub Option<T>);
+
+impl<T: Display> Display for Answer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            Some(answer) => answer.fmt(f),
+            None => write!(f, "(no answer)"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Phase {
+    pub name: &'static str,
+    pub answer: Option<String>,
+    pub elapsed: Duration,
+    // only measured when memory counting is enabled
+    pub memory: Option<MemoryUsage>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Report {
+    pub phases: Vec<Phase>,
+}
+
+impl Report {
+    pub fn answers(&self) -> impl Iterator<Item = (&'static str, &str)> {
+        self.phases
+            .iter()
+            .filter_map(|phase| phase.answer.as_deref().map(|answer| (phase.name, answer)))
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.phases.iter().map(|phase| phase.elapsed).sum()
+    }
+
+    fn record<T, F>(&mut self, name: &'static str, f: F) -> T
+    where
+        F: FnOnce() -> T,
+    {
+        let _span = crate::span!(Info, name);
+        let start = Instant::now();
+        let (result, memory) = memory::measure(f);
+        self.phases.push(Phase {
+            name,
+            answer: None,
+            elapsed: start.elapsed(),
+            memory,
+        });
+        result
+    }
+
+    fn record_answer<T, F>(&mut self, name: &'static str, f: F)
+    where
+        T: Display,
+        F: FnOnce() -> T,
+    {
+        let answer = self.record(name, f).to_string();
+        self.phases.last_mut().unwrap().answer = Some(answer);
+    }
+}
+
+pub fn run<S: Solution>(src: &str) -> Result<Report, String> {
+    let mut report = Report::default();
+    let input = report.record("Parse", || S::parse(src))?;
+    report.record_answer("Part 1", || S::part1(&input));
+    report.record_answer("Part 2", || S::part2(&input));
+
+    Ok(report)
+}
+
+// the day's own input, for binaries that query it beyond the two parts
+pub fn load_input<S: Solution>(year: u16, day: u8) -> Result<S::Input, String> {
+    S::parse(&load_raw_input(year, day)?)
+}
+
+pub fn load_raw_input(year: u16, day: u8) -> Result<String, String> {
+    InputCache::from_env().get(year, day)
+}
+
+pub fn load_input_file<S: Solution>(file_name: &str) -> Result<S::Input, String> {
+    let src = std::fs::read_to_string(file_name).map_err(|e| format!("{}: {}", file_name, e))?;
+    S::parse(&src).map_err(|e| format!("{}: {}", file_name, e))
+}
+
+// the day's input from the cache, parsed and solved
+pub fn solve<S: Solution>(cache: &InputCache, year: u16, day: u8) -> Result<Report, String> {
+    run::<S>(&cache.get(year, day)?)
+}
+
+// a missing or bad input is reported, not panicked over, like the commands' errors
+pub fn run_main<S: Solution>(year: u16, day: u8) {
+    match solve::<S>(&InputCache::from_env(), year, day) {
+        Ok(report) => {
+            for (name, answer) in report.answers() {
+                println!("{}: {}", name, answer);
+            }
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+
+    // a fresh directory under the system temp dir, removed when dropped
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let path =
+                std::env::temp_dir().join(format!("aoc-solution-{}-{}", std::process::id(), name));
+            let _ = fs::remove_dir_all(&path);
+            fs::create_dir_all(&path).unwrap();
+            TempDir(path)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    // sums the numbers on each line, and counts them
+    struct Sum;
+
+    impl Solution for Sum {
+        type Input = Vec<i64>;
+        type Part1 = i64;
+        type Part2 = usize;
+
+        fn parse(src: &str) -> Result<Vec<i64>, String> {
+            src.lines()
+                .map(|line| {
+                    line.parse()
+                        .map_err(|_| format!("not a number: {:?}", line))
+                })
+                .collect()
+        }
+
+        fn part1(input: &Vec<i64>) -> i64 {
+            input.iter().sum()
+        }
+
+        fn part2(input: &Vec<i64>) -> usize {
+            input.len()
+        }
+    }
+
+    fn cache_with(dir: &TempDir, src: &str) -> InputCache {
+        let cache = InputCache::new(&dir.0);
+        let path = cache.path(2020, 1);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, src).unwrap();
+        cache
+    }
+
+    #[test]
+    fn solves_from_the_cache() {
+        let dir = TempDir::new("solves");
+        let report = solve::<Sum>(&cache_with(&dir, "1\n2\n39\n"), 2020, 1).unwrap();
+        let answers: Vec<_> = report.answers().collect();
+        assert_eq!(answers, [("Part 1", "42"), ("Part 2", "3")]);
+        assert_eq!(report.phases[0].name, "Parse");
+    }
+
+    #[test]
+    fn bad_and_missing_inputs_are_errors() {
+        let dir = TempDir::new("bad");
+        assert_eq!(
+            solve::<Sum>(&cache_with(&dir, "1\ntwo\n"), 2020, 1).unwrap_err(),
+            "not a number: \"two\""
+        );
+        assert!(solve::<Sum>(&InputCache::new(&dir.0), 2020, 2).is_err());
+    }
+}
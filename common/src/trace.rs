@@ -0,0 +1,505 @@
+//! A small logging facade for the solvers. `span!` and `event!` compile to nothing unless
+//! `common` is built with the `trace` feature, so instrumentation costs nothing by default;
+//! with it, a disabled level costs one relaxed load.
+
+use std::fmt::{self, Display};
+use std::io::Write;
+use std::str::FromStr;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Error = 1,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl FromStr for Level {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match &s.to_ascii_lowercase()[..] {
+            "error" => Ok(Level::Error),
+            "warn" => Ok(Level::Warn),
+            "info" => Ok(Level::Info),
+            "debug" => Ok(Level::Debug),
+            "trace" => Ok(Level::Trace),
+            _ => Err(format!("Invalid log level: {}", s)),
+        }
+    }
+}
+
+impl Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Level::Error => "error",
+            Level::Warn => "warn",
+            Level::Info => "info",
+            Level::Debug => "debug",
+            Level::Trace => "trace",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    // one JSON object per line
+    Json,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Int(i64),
+    Uint(u64),
+    Float(f64),
+    Bool(bool),
+    Str(String),
+}
+
+macro_rules! value_from {
+    ($variant:ident as $target:ty: $($t:ty),*) => {
+        $(impl From<$t> for Value {
+            fn from(value: $t) -> Self {
+                Value::$variant(value as $target)
+            }
+        })*
+    };
+}
+
+value_from!(Int as i64: i8, i16, i32, i64, isize);
+value_from!(Uint as u64: u8, u16, u32, u64, usize);
+value_from!(Float as f64: f32, f64);
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Bool(value)
+    }
+}
+
+impl From<char> for Value {
+    fn from(value: char) -> Self {
+        Value::Str(value.to_string())
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::Str(value.into())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::Str(value)
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Int(v) => write!(f, "{}", v),
+            Value::Uint(v) => write!(f, "{}", v),
+            Value::Float(v) => write!(f, "{}", v),
+            Value::Bool(v) => write!(f, "{}", v),
+            Value::Str(v) => write!(f, "{:?}", v),
+        }
+    }
+}
+
+pub type Fields = Vec<(&'static str, Value)>;
+
+/// Sends everything at `level` or more severe to `out`. Fails if tracing was compiled
+/// out, or if it has already been set up.
+pub fn init(level: Level, format: Format, out: Box<dyn Write + Send>) -> Result<(), String> {
+    imp::init(level, format, out)
+}
+
+pub use imp::{enabled, Span};
+
+#[doc(hidden)]
+pub use imp::emit_event;
+
+#[cfg(not(feature = "trace"))]
+mod imp {
+    use super::{Format, Level};
+    use std::io::Write;
+
+    pub fn init(_: Level, _: Format, _: Box<dyn Write + Send>) -> Result<(), String> {
+        Err("Logging was compiled out; rebuild with the `trace` feature".into())
+    }
+
+    pub fn enabled(_: Level) -> bool {
+        false
+    }
+
+    #[doc(hidden)]
+    pub fn emit_event(_: Level, _: &'static str, _: &'static str, _: super::Fields) {}
+
+    pub struct Span;
+}
+
+#[cfg(feature = "trace")]
+mod imp {
+    use super::{Fields, Format, Level, Value};
+    use crate::escape::push_json_str;
+    use std::cell::RefCell;
+    use std::fmt::Write as _;
+    use std::io::Write;
+    use std::sync::atomic::{AtomicU8, Ordering::Relaxed};
+    use std::sync::{Mutex, OnceLock};
+    use std::time::Instant;
+
+    static MAX_LEVEL: AtomicU8 = AtomicU8::new(0);
+    static SINK: OnceLock<Sink> = OnceLock::new();
+
+    struct Sink {
+        format: Format,
+        start: Instant,
+        out: Mutex<Box<dyn Write + Send>>,
+    }
+
+    struct OpenSpan {
+        name: &'static str,
+        fields: Fields,
+    }
+
+    thread_local! {
+        // the spans entered on this thread, outermost first
+        static SPANS: RefCell<Vec<OpenSpan>> = const { RefCell::new(Vec::new()) };
+    }
+
+    pub fn init(level: Level, format: Format, out: Box<dyn Write + Send>) -> Result<(), String> {
+        let sink = Sink {
+            format,
+            start: Instant::now(),
+            out: Mutex::new(out),
+        };
+        SINK.set(sink)
+            .map_err(|_| "Logging has already been set up".to_string())?;
+        MAX_LEVEL.store(level as u8, Relaxed);
+        Ok(())
+    }
+
+    pub fn enabled(level: Level) -> bool {
+        level as u8 <= MAX_LEVEL.load(Relaxed)
+    }
+
+    #[doc(hidden)]
+    pub fn emit_event(level: Level, target: &'static str, name: &'static str, fields: Fields) {
+        emit("event", level, target, name, &fields);
+    }
+
+    // logged when it closes, with how long it was open
+    pub struct Span {
+        open: Option<(Level, &'static str, Instant)>,
+    }
+
+    impl Span {
+        #[doc(hidden)]
+        pub fn enter<F>(level: Level, target: &'static str, name: &'static str, fields: F) -> Self
+        where
+            F: FnOnce() -> Fields,
+        {
+            if !enabled(level) {
+                return Span { open: None };
+            }
+            let fields = fields();
+            SPANS.with(|spans| spans.borrow_mut().push(OpenSpan { name, fields }));
+            Span {
+                open: Some((level, target, Instant::now())),
+            }
+        }
+    }
+
+    impl Drop for Span {
+        fn drop(&mut self) {
+            if let Some((level, target, start)) = self.open {
+                let elapsed = start.elapsed().as_micros() as u64;
+                let span = SPANS.with(|spans| spans.borrow_mut().pop());
+                if let Some(OpenSpan { name, mut fields }) = span {
+                    fields.push(("elapsed_us", Value::Uint(elapsed)));
+                    emit("span", level, target, name, &fields);
+                }
+            }
+        }
+    }
+
+    fn emit(kind: &str, level: Level, target: &str, name: &str, fields: &Fields) {
+        let sink = match SINK.get() {
+            Some(sink) => sink,
+            None => return,
+        };
+        let elapsed = sink.start.elapsed().as_micros() as u64;
+        let thread = std::thread::current();
+        let thread = thread.name().unwrap_or("main");
+
+        let line = SPANS.with(|spans| match sink.format {
+            Format::Text => text_line(level, elapsed, thread, &spans.borrow(), name, fields),
+            Format::Json => json_line(
+                kind,
+                level,
+                elapsed,
+                thread,
+                target,
+                &spans.borrow(),
+                name,
+                fields,
+            ),
+        });
+        // a broken log sink shouldn't take the solvers down with it
+        if let Ok(mut out) = sink.out.lock() {
+            let _ = out.write_all(line.as_bytes());
+        }
+    }
+
+    fn text_line(
+        level: Level,
+        elapsed: u64,
+        thread: &str,
+        spans: &[OpenSpan],
+        name: &str,
+        fields: &Fields,
+    ) -> String {
+        let mut line = format!(
+            "{:>10.3}ms {:5} {}: ",
+            elapsed as f64 / 1000.0,
+            level.to_string().to_uppercase(),
+            thread
+        );
+        for span in spans {
+            line.push_str(span.name);
+            if !span.fields.is_empty() {
+                line.push('{');
+                push_text_fields(&mut line, &span.fields);
+                line.push('}');
+            }
+            line.push_str(": ");
+        }
+        line.push_str(name);
+        if !fields.is_empty() {
+            line.push(' ');
+            push_text_fields(&mut line, fields);
+        }
+        line.push('\n');
+        line
+    }
+
+    fn push_text_fields(line: &mut String, fields: &Fields) {
+        for (idx, (key, value)) in fields.iter().enumerate() {
+            if idx > 0 {
+                line.push(' ');
+            }
+            let _ = write!(line, "{}={}", key, value);
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn json_line(
+        kind: &str,
+        level: Level,
+        elapsed: u64,
+        thread: &str,
+        target: &str,
+        spans: &[OpenSpan],
+        name: &str,
+        fields: &Fields,
+    ) -> String {
+        let mut line = format!(
+            "{{\"elapsed_us\":{},\"level\":\"{}\",\"kind\":\"{}\",\"thread\":",
+            elapsed, level, kind
+        );
+        push_json_str(&mut line, thread);
+        line.push_str(",\"target\":");
+        push_json_str(&mut line, target);
+        line.push_str(",\"name\":");
+        push_json_str(&mut line, name);
+        line.push_str(",\"fields\":");
+        push_json_fields(&mut line, fields);
+        line.push_str(",\"spans\":[");
+        for (idx, span) in spans.iter().enumerate() {
+            if idx > 0 {
+                line.push(',');
+            }
+            line.push_str("{\"name\":");
+            push_json_str(&mut line, span.name);
+            line.push_str(",\"fields\":");
+            push_json_fields(&mut line, &span.fields);
+            line.push('}');
+        }
+        line.push_str("]}\n");
+        line
+    }
+
+    fn push_json_fields(line: &mut String, fields: &Fields) {
+        line.push('{');
+        for (idx, (key, value)) in fields.iter().enumerate() {
+            if idx > 0 {
+                line.push(',');
+            }
+            push_json_str(line, key);
+            line.push(':');
+            match value {
+                Value::Str(s) => push_json_str(line, s),
+                Value::Float(f) if !f.is_finite() => line.push_str("null"),
+                other => {
+                    let _ = write!(line, "{}", other);
+                }
+            }
+        }
+        line.push('}');
+    }
+}
+
+/// Logs an event, e.g. `event!(Debug, "elimination round", round = 3, unresolved = 5)`.
+/// The field values are only evaluated when the level is enabled.
+#[cfg(feature = "trace")]
+#[macro_export]
+macro_rules! event {
+    ($level:ident, $name:expr $(, $key:ident = $value:expr)* $(,)?) => {
+        if $crate::trace::enabled($crate::trace::Level::$level) {
+            $crate::trace::emit_event(
+                $crate::trace::Level::$level,
+                module_path!(),
+                $name,
+                vec![$((stringify!($key), $crate::trace::Value::from($value))),*],
+            );
+        }
+    };
+}
+
+// the values are still borrowed, so that a variable only used for logging isn't reported
+// as unused, but never evaluated
+#[cfg(not(feature = "trace"))]
+#[macro_export]
+macro_rules! event {
+    ($level:ident, $name:expr $(, $key:ident = $value:expr)* $(,)?) => {
+        if false {
+            $(let _ = &$value;)*
+        }
+    };
+}
+
+/// Opens a span that lasts until the returned guard is dropped, e.g.
+/// `let _span = span!(Debug, "game", depth = 2);`. Events logged meanwhile on the same
+/// thread are tagged with it.
+#[cfg(feature = "trace")]
+#[macro_export]
+macro_rules! span {
+    ($level:ident, $name:expr $(, $key:ident = $value:expr)* $(,)?) => {
+        $crate::trace::Span::enter($crate::trace::Level::$level, module_path!(), $name, || {
+            vec![$((stringify!($key), $crate::trace::Value::from($value))),*]
+        })
+    };
+}
+
+#[cfg(not(feature = "trace"))]
+#[macro_export]
+macro_rules! span {
+    ($level:ident, $name:expr $(, $key:ident = $value:expr)* $(,)?) => {{
+        if false {
+            $(let _ = &$value;)*
+        }
+        $crate::trace::Span
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "trace")]
+    #[test]
+    fn json_lines_at_or_above_the_level() {
+        use std::sync::{Arc, Mutex};
+
+        #[derive(Clone)]
+        struct Shared(Arc<Mutex<Vec<u8>>>);
+
+        impl Write for Shared {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().write(buf)
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let out = Shared(Arc::new(Mutex::new(vec![])));
+        init(Level::Info, Format::Json, Box::new(out.clone())).unwrap();
+        assert!(init(Level::Info, Format::Json, Box::new(out.clone())).is_err());
+        assert!(enabled(Level::Warn) && enabled(Level::Info) && !enabled(Level::Debug));
+
+        let evaluated = std::cell::Cell::new(false);
+        crate::event!(
+            Debug,
+            "hidden",
+            x = {
+                evaluated.set(true);
+                1
+            }
+        );
+        assert!(!evaluated.get());
+        {
+            let _span = crate::span!(Info, "outer", day = 7u8);
+            crate::event!(Warn, "solved", part = 1, answer = "a \"b\"\n", ok = true);
+        }
+
+        let logged = String::from_utf8(out.0.lock().unwrap().clone()).unwrap();
+        // anything else running in the test binary may log too
+        let lines: Vec<_> = logged
+            .lines()
+            .filter(|line| line.contains("\"target\":\"common::trace::tests\""))
+            .collect();
+        assert_eq!(lines.len(), 2);
+        let thread = crate::escape::json_str(std::thread::current().name().unwrap());
+        let rest = |line: &str| line[line.find(",\"level\"").unwrap()..].to_string();
+        assert!(lines[0].starts_with("{\"elapsed_us\":"));
+        assert_eq!(
+            rest(lines[0]),
+            format!(
+                ",\"level\":\"warn\",\"kind\":\"event\",\"thread\":{},\
+                 \"target\":\"common::trace::tests\",\"name\":\"solved\",\
+                 \"fields\":{{\"part\":1,\"answer\":\"a \\\"b\\\"\\n\",\"ok\":true}},\
+                 \"spans\":[{{\"name\":\"outer\",\"fields\":{{\"day\":7}}}}]}}",
+                thread
+            )
+        );
+        assert!(rest(lines[1]).starts_with(&format!(
+            ",\"level\":\"info\",\"kind\":\"span\",\"thread\":{},\
+             \"target\":\"common::trace::tests\",\"name\":\"outer\",\
+             \"fields\":{{\"day\":7,\"elapsed_us\":",
+            thread
+        )));
+        assert!(lines[1].ends_with(",\"spans\":[]}"));
+    }
+
+    #[cfg(not(feature = "trace"))]
+    #[test]
+    fn compiled_out_without_the_feature() {
+        let evaluated = std::cell::Cell::new(false);
+        let _span = crate::span!(
+            Error,
+            "span",
+            x = {
+                evaluated.set(true);
+                1
+            }
+        );
+        crate::event!(
+            Error,
+            "event",
+            x = {
+                evaluated.set(true);
+                1
+            }
+        );
+        assert!(!evaluated.get());
+        assert_eq!(std::mem::size_of_val(&_span), 0);
+        assert!(!enabled(Level::Error));
+        assert!(init(Level::Trace, Format::Text, Box::new(std::io::sink())).is_err());
+    }
+}